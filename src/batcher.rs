@@ -1,9 +1,8 @@
-use crate::e::{ErrorKind, S5Error};
-use reqwest::{
-    self,
-    header::{HeaderMap, AUTHORIZATION},
-    Certificate,
-};
+use crate::e::S5Error;
+use crate::http;
+use crate::routes;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -18,23 +17,35 @@ pub enum ResponseType {
     List(ListBatchersResponse),
 }
 
+/// The batcher wraps every response in a result/error envelope
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct CBatcherResponse {
-    pub result: CreateBatcherResponse,
+pub struct BatcherResponse<T> {
+    pub result: Option<T>,
     pub error: Option<String>,
 }
-impl CBatcherResponse {
-    pub fn from_str(stringified: &str) -> Result<CBatcherResponse, S5Error> {
+impl<T: DeserializeOwned> BatcherResponse<T> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<BatcherResponse<T>, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+    /// Used internally to surface the gatekeeper reported error
+    pub fn into_result(self, stringified: &str) -> Result<T, S5Error> {
+        match (self.result, self.error) {
+            (_, Some(error)) => Err(S5Error::Gatekeeper(error)),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(S5Error::Deserialize {
+                message: "missing result".to_string(),
+                body: stringified.to_string(),
+            }),
         }
     }
 }
+
+pub type CBatcherResponse = BatcherResponse<CreateBatcherResponse>;
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateBatcherRequest {
@@ -56,52 +67,24 @@ pub struct CreateBatcherResponse {
     pub batcher_id: u64,
 }
 impl CreateBatcherResponse {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<CreateBatcherResponse, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 ///Used to create a batching template, by setting a label and a default confTarget.
 pub async fn createbatcher(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: CreateBatcherRequest,
-) -> Result<CreateBatcherResponse, String> {
-    let full_url: String = format!("https://{}/v0/createbatcher", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match CBatcherResponse::from_str(&text) {
-                    Ok(result) => {
-                        if result.error.is_none(){
-                            Ok(result.result)
-                        }
-                        else{
-                            Err(result.error.unwrap().to_string())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
-    }
+) -> Result<CreateBatcherResponse, S5Error> {
+    let full_url: String = routes::CREATEBATCHER.url(&base_url);
+    let text = http::post(client, &routes::CREATEBATCHER, &full_url, jwt, &body).await?;
+    CBatcherResponse::from_str(&text)?.into_result(&text)
 }
 
 
@@ -133,69 +116,25 @@ pub struct UpdateBatcherResponse {
     pub conf_target: u64,
 }
 impl UpdateBatcherResponse {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<UpdateBatcherResponse, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
-        }
-    }
-}
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UBatcherResponse {
-    pub result: UpdateBatcherResponse,
-    pub error: Option<String>,
-}
-impl UBatcherResponse {
-    pub fn from_str(stringified: &str) -> Result<UBatcherResponse, S5Error> {
-        match serde_json::from_str(stringified) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
+pub type UBatcherResponse = BatcherResponse<UpdateBatcherResponse>;
 ///Used to change batching template settings.
 pub async fn updatebatcher(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: UpdateBatcherRequest,
-) -> Result<UpdateBatcherResponse, String> {
-    let full_url: String = format!("https://{}/v0/updatebatcher", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match UBatcherResponse::from_str(&text) {
-                    Ok(result) => {
-                        if result.error.is_none(){
-                            Ok(result.result)
-                        }
-                        else{
-                            Err(result.error.unwrap().to_string())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
-    }
+) -> Result<UpdateBatcherResponse, S5Error> {
+    let full_url: String = routes::UPDATEBATCHER.url(&base_url);
+    let text = http::post(client, &routes::UPDATEBATCHER, &full_url, jwt, &body).await?;
+    UBatcherResponse::from_str(&text)?.into_result(&text)
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -231,69 +170,25 @@ pub struct BatchInfoResponse {
     pub total: f64,
 }
 impl BatchInfoResponse {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<BatchInfoResponse, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
-        }
-    }
-}
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct IBatcherResponse {
-    pub result: BatchInfoResponse,
-    pub error: Option<String>,
-}
-impl IBatcherResponse {
-    pub fn from_str(stringified: &str) -> Result<IBatcherResponse, S5Error> {
-        match serde_json::from_str(stringified) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
+pub type IBatcherResponse = BatcherResponse<BatchInfoResponse>;
 ///Inserts output information in the DB. Used when batchspend is called later.
 pub async fn addtobatch(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: AddToBatchRequest,
-) -> Result<BatchInfoResponse, String> {
-    let full_url: String = format!("https://{}/v0/addtobatch", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match IBatcherResponse::from_str(&text) {
-                    Ok(result) => {
-                        if result.error.is_none(){
-                            Ok(result.result)
-                        }
-                        else{
-                            Err(result.error.unwrap().to_string())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
-    }
+) -> Result<BatchInfoResponse, S5Error> {
+    let full_url: String = routes::ADDTOBATCH.url(&base_url);
+    let text = http::post(client, &routes::ADDTOBATCH, &full_url, jwt, &body).await?;
+    IBatcherResponse::from_str(&text)?.into_result(&text)
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -308,40 +203,14 @@ impl RemoveFromBatchRequest {
 }
 ///Removes a previously added output scheduled for the next batch.
 pub async fn removefrombatch(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: RemoveFromBatchRequest,
-) -> Result<BatchInfoResponse, String> {
-    let full_url: String = format!("https://{}/v0/removefrombatch", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match IBatcherResponse::from_str(&text) {
-                    Ok(result) => {
-                        if result.error.is_none(){
-                            Ok(result.result)
-                        }
-                        else{
-                            Err(result.error.unwrap().to_string())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
-    }
+) -> Result<BatchInfoResponse, S5Error> {
+    let full_url: String = routes::REMOVEFROMBATCH.url(&base_url);
+    let text = http::post(client, &routes::REMOVEFROMBATCH, &full_url, jwt, &body).await?;
+    IBatcherResponse::from_str(&text)?.into_result(&text)
     
 }
 
@@ -362,40 +231,14 @@ impl GetBatcherRequest {
 }
 ///Will return current state/summary of the requested batching template.
 pub async fn getbatcher(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: GetBatcherRequest,
-) -> Result<BatchInfoResponse, String> {
-    let full_url: String = format!("https://{}/v0/getbatcher", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match IBatcherResponse::from_str(&text) {
-                    Ok(result) => {
-                        if result.error.is_none(){
-                            Ok(result.result)
-                        }
-                        else{
-                            Err(result.error.unwrap().to_string())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
-    }
+) -> Result<BatchInfoResponse, S5Error> {
+    let full_url: String = routes::GETBATCHER.url(&base_url);
+    let text = http::post(client, &routes::GETBATCHER, &full_url, jwt, &body).await?;
+    IBatcherResponse::from_str(&text)?.into_result(&text)
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -426,71 +269,27 @@ pub struct BatchSpendResponse {
 }
 impl BatchSpendResponse {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<BatchSpendResponse, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct SBatcherResponse {
-    pub result: BatchSpendResponse,
-    pub error: Option<String>,
-}
-impl SBatcherResponse {
-    pub fn from_str(stringified: &str) -> Result<SBatcherResponse, S5Error> {
-        match serde_json::from_str(stringified) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
-        }
-    }
-}
+pub type SBatcherResponse = BatcherResponse<BatchSpendResponse>;
 /// Calls the sendmany RPC on spending wallet with the unspent "addtobatch" inserted outputs. 
 /// Will execute default batcher if no batcherId/batcherLabel supplied and default confTarget if no confTarget supplied.
 pub async fn batchspend(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: BatchSpendRequest,
-) -> Result<BatchSpendResponse, String> {
-    let full_url: String = format!("https://{}/v0/batchspend", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match SBatcherResponse::from_str(&text) {
-                    Ok(result) => {
-                        if result.error.is_none(){
-                            Ok(result.result)
-                        }
-                        else{
-                            Err(result.error.unwrap().to_string())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
-    }
+) -> Result<BatchSpendResponse, S5Error> {
+    let full_url: String = routes::BATCHSPEND.url(&base_url);
+    let text = http::post(client, &routes::BATCHSPEND, &full_url, jwt, &body).await?;
+    SBatcherResponse::from_str(&text)?.into_result(&text)
 }
 
 
@@ -536,13 +335,11 @@ pub struct BatchDetailResponse {
 }
 impl BatchDetailResponse {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<BatchDetailResponse, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -558,61 +355,19 @@ pub struct Output {
 }
 
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BDBatcherResponse {
-    pub result: BatchDetailResponse,
-    pub error: Option<String>,
-}
-impl BDBatcherResponse {
-    pub fn from_str(stringified: &str) -> Result<BDBatcherResponse, S5Error> {
-        match serde_json::from_str(stringified) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
-        }
-    }
-}
+pub type BDBatcherResponse = BatcherResponse<BatchDetailResponse>;
 ///Will return current state and details of the requested batch, including all outputs. 
 ///A batch is the combination of a batcher and an optional txid. 
 /// If no txid is supplied, will return current non-yet-executed batch.
 pub async fn getbatchdetails(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: GetBatchDetailRequest,
-) -> Result<BatchDetailResponse, String> {
-    let full_url: String = format!("https://{}/v0/getbatchdetails", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match BDBatcherResponse::from_str(&text) {
-                    Ok(result) => {
-                        if result.error.is_none(){
-                            Ok(result.result)
-                        }
-                        else{
-                            Err(result.error.unwrap().to_string())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
-    }
+) -> Result<BatchDetailResponse, S5Error> {
+    let full_url: String = routes::GETBATCHDETAILS.url(&base_url);
+    let text = http::post(client, &routes::GETBATCHDETAILS, &full_url, jwt, &body).await?;
+    BDBatcherResponse::from_str(&text)?.into_result(&text)
 }
 
 
@@ -630,72 +385,29 @@ pub struct ListBatchersResponse {
 }
 impl ListBatchersResponse {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<ListBatchersResponse, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
-        }
-    }
-}
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LBatcherResponse {
-    pub result: Batchers,
-    pub error: Option<String>,
-}
-impl LBatcherResponse {
-    pub fn from_str(stringified: &str) -> Result<LBatcherResponse, S5Error> {
-        match serde_json::from_str(stringified) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
+pub type LBatcherResponse = BatcherResponse<Batchers>;
 // ///Will return a list of batch templates. 
 // ///batcherId 1 is a default batcher created at installation time.
 
 pub async fn listbatchers(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<Batchers, String> {
-    let full_url: String = format!("https://{}/v0/listbatchers", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LBatcherResponse::from_str(&text) {
-                    Ok(result) => {
-                        if result.error.is_none(){
-                            Ok(result.result)
-                        }
-                        else{
-                            Err(result.error.unwrap().to_string())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
-    }
+    client: &Client,
+) -> Result<Batchers, S5Error> {
+    let full_url: String = routes::LISTBATCHERS.url(&base_url);
+    let text = http::get(client, &routes::LISTBATCHERS, &full_url, jwt).await?;
+    LBatcherResponse::from_str(&text)?.into_result(&text)
 }
 
+/// Posted by cyphernode to the webhookUrl of each output once its batch is spent
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BatcherCallback {
@@ -712,6 +424,16 @@ pub struct BatcherCallback {
     pub hash: String,
     pub details: Details,
 }
+impl BatcherCallback {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<BatcherCallback, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
 
 
 // // GET http://cyphernode:8888/batchspend
@@ -0,0 +1,205 @@
+//! Notifications cyphernode posts back to the app.
+//!
+//! Every callback url handed to cyphernode (watch unconfirmedCallbackURL/confirmedCallbackURL, watchxpub, watchtxid,
+//! addtobatch webhookUrl, ln_connectfund and invoice callbackUrl) can point at a `CallbackListener`,
+//! which parses each post into a [`CallbackEvent`]. The listener needs the `callbacks` feature;
+//! [`CallbackEvent::from_str`] is always available for apps that already run their own http server.
+use crate::batcher::BatcherCallback;
+use crate::e::S5Error;
+use crate::lightning::{LnConnectFundCallback, LnInvoiceCallback};
+use crate::watcher::{WatchCallback, WatchTxidCallback};
+use serde_json::Value;
+
+/// A typed cyphernode notification
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallbackEvent {
+    /// A watched address received a transaction, not yet mined
+    AddressZeroConf(WatchCallback),
+    /// A transaction to a watched address got its first confirmation
+    AddressConfirmed(WatchCallback),
+    /// A transaction to an address derived from a watched xpub (0 or 1 conf, see confirmations)
+    XpubTx(WatchCallback),
+    /// A watched txid reached 1 confirmation, or nbxconf confirmations
+    TxidConfirmed(WatchTxidCallback),
+    /// The batch holding an output added with a webhookUrl was spent
+    BatchSpent(BatcherCallback),
+    /// ln_connectfund finished; result is "success" or "failed"
+    ChannelFunded(LnConnectFundCallback),
+    /// An invoice created with a callbackUrl was paid
+    InvoicePaid(LnInvoiceCallback),
+}
+impl CallbackEvent {
+    /// Recognise a callback body by its fields; callback urls are chosen by the app, so the path says nothing
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<CallbackEvent, S5Error> {
+        let value: Value = match serde_json::from_str(stringified) {
+            Ok(result) => result,
+            Err(e) => return Err(S5Error::deserialize(e, stringified)),
+        };
+        let has = |field: &str| value.get(field).is_some_and(|v| !v.is_null());
+        if has("batcherId") && has("outputId") {
+            Ok(CallbackEvent::BatchSpent(BatcherCallback::from_str(stringified)?))
+        } else if has("bolt11") && has("payment_hash") {
+            Ok(CallbackEvent::InvoicePaid(LnInvoiceCallback::from_str(stringified)?))
+        } else if has("result") && (has("channel_id") || has("message")) {
+            Ok(CallbackEvent::ChannelFunded(LnConnectFundCallback::from_str(stringified)?))
+        } else if has("txid") && has("confirmations") && !has("address") {
+            Ok(CallbackEvent::TxidConfirmed(WatchTxidCallback::from_str(stringified)?))
+        } else if has("address") && has("hash") {
+            let tx = WatchCallback::from_str(stringified)?;
+            if tx.pub32.is_some() {
+                Ok(CallbackEvent::XpubTx(tx))
+            } else if tx.confirmations == 0 {
+                Ok(CallbackEvent::AddressZeroConf(tx))
+            } else {
+                Ok(CallbackEvent::AddressConfirmed(tx))
+            }
+        } else {
+            Err(S5Error::Input(format!("Unknown callback ({} byte body)", stringified.len())))
+        }
+    }
+}
+
+/// A callback event and the path it was posted to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Callback {
+    pub path: String,
+    pub event: CallbackEvent,
+}
+
+#[cfg(any(test, feature = "callbacks"))]
+pub use listener::{CallbackListener, MAX_BODY};
+
+#[cfg(any(test, feature = "callbacks"))]
+mod listener {
+    use super::{Callback, CallbackEvent};
+    use crate::e::S5Error;
+    use hyper::body::HttpBody;
+    use hyper::header::CONTENT_LENGTH;
+    use hyper::server::conn::Http;
+    use hyper::service::service_fn;
+    use hyper::{Body, Method, Request, Response, StatusCode};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio::task::JoinHandle;
+
+    /// Events waiting to be read before cyphernode's posts start to block
+    const BUFFER: usize = 256;
+    /// Largest callback body read; cyphernode's posts are a few KB at most
+    pub const MAX_BODY: usize = 256 * 1024;
+
+    /// Embedded http server receiving cyphernode callbacks on any path; stopped on drop.
+    ///
+    /// Answers 200 once an event is queued, 400 to bodies it does not recognise, 405 to anything but POST
+    /// and 413 to bodies over [`MAX_BODY`], so cyphernode retries what was not delivered.
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), cngateway::e::S5Error> {
+    /// use cngateway::callback::{CallbackEvent, CallbackListener};
+    ///
+    /// let mut listener = CallbackListener::bind("0.0.0.0:1111").await?;
+    /// // watch(address, "http://myapp:1111/callback0conf", "http://myapp:1111/callback1conf", ...)
+    /// while let Some(callback) = listener.next().await {
+    ///     match callback.event {
+    ///         CallbackEvent::AddressConfirmed(tx) => println!("{} confirmed", tx.hash),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub struct CallbackListener {
+        local_addr: SocketAddr,
+        events: mpsc::Receiver<Callback>,
+        server: JoinHandle<()>,
+    }
+    impl CallbackListener {
+        /// Listen on addr, eg. 0.0.0.0:1111; port 0 picks a free port
+        pub async fn bind(addr: impl ToString) -> Result<CallbackListener, S5Error> {
+            let listener = match TcpListener::bind(addr.to_string()).await {
+                Ok(result) => result,
+                Err(e) => return Err(S5Error::Transport(e.to_string())),
+            };
+            let local_addr = match listener.local_addr() {
+                Ok(result) => result,
+                Err(e) => return Err(S5Error::Transport(e.to_string())),
+            };
+            let (sender, events) = mpsc::channel(BUFFER);
+            let server = tokio::spawn(async move {
+                while let Ok((tcp, _)) = listener.accept().await {
+                    let sender = sender.clone();
+                    tokio::spawn(async move {
+                        let service = service_fn(move |request| handle(sender.clone(), request));
+                        let _ = Http::new().serve_connection(tcp, service).await;
+                    });
+                }
+            });
+            Ok(CallbackListener {
+                local_addr,
+                events,
+                server,
+            })
+        }
+        /// Address actually bound
+        pub fn local_addr(&self) -> SocketAddr {
+            self.local_addr
+        }
+        /// Wait for the next callback; None once the listener has stopped
+        pub async fn next(&mut self) -> Option<Callback> {
+            self.events.recv().await
+        }
+    }
+    impl Drop for CallbackListener {
+        fn drop(&mut self) {
+            self.server.abort();
+        }
+    }
+
+    async fn handle(
+        sender: mpsc::Sender<Callback>,
+        request: Request<Body>,
+    ) -> Result<Response<Body>, Infallible> {
+        if request.method() != Method::POST {
+            return Ok(reply(StatusCode::METHOD_NOT_ALLOWED));
+        }
+        let path = request.uri().path().to_string();
+        let declared = request
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse::<usize>().ok());
+        if declared.is_some_and(|length| length > MAX_BODY) {
+            return Ok(reply(StatusCode::PAYLOAD_TOO_LARGE));
+        }
+        // chunked bodies carry no length, so the cap is also enforced while reading
+        let mut body = request.into_body();
+        let mut bytes = Vec::with_capacity(declared.unwrap_or(0));
+        while let Some(chunk) = body.data().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(_) => return Ok(reply(StatusCode::BAD_REQUEST)),
+            };
+            if bytes.len() + chunk.len() > MAX_BODY {
+                return Ok(reply(StatusCode::PAYLOAD_TOO_LARGE));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        let body = String::from_utf8_lossy(&bytes).to_string();
+        let event = match CallbackEvent::from_str(&body) {
+            Ok(event) => event,
+            Err(_) => return Ok(reply(StatusCode::BAD_REQUEST)),
+        };
+        match sender.send(Callback { path, event }).await {
+            Ok(()) => Ok(reply(StatusCode::OK)),
+            Err(_) => Ok(reply(StatusCode::SERVICE_UNAVAILABLE)),
+        }
+    }
+
+    fn reply(status: StatusCode) -> Response<Body> {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = status;
+        response
+    }
+}
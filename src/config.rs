@@ -1,7 +1,339 @@
 // take in auth creds
-// tests connection to cyphernode proxy
 /*
+CYPHERNODE_API_KID=003
 CYPHERNODE_API_KEY=
 CYPHER_GATEWAY_URL=https://localhost:2009/v0/
 CYPHERNODE_GATEKEEPER_CERT_CA=$(cat ~/cyphernode/dist/cyphernode/gatekeeper/certs.pem)
-*/
\ No newline at end of file
+*/
+use crate::e::S5Error;
+use crate::keys::KeyRing;
+use crate::{CnGateway, LIFETIME, POOL_IDLE_TIMEOUT, TCP_KEEPALIVE};
+use reqwest::{Certificate, Client, Url};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+pub const ENV_KID: &str = "CYPHERNODE_API_KID";
+pub const ENV_KEY: &str = "CYPHERNODE_API_KEY";
+pub const ENV_URL: &str = "CYPHER_GATEWAY_URL";
+pub const ENV_CERT: &str = "CYPHERNODE_GATEKEEPER_CERT_CA";
+pub const ENV_KEYS_FILE: &str = "CYPHERNODE_KEYS_FILE";
+
+const DEFAULT_SCHEME: &str = "https";
+const DEFAULT_BASE_PATH: &str = "/v0";
+const DEFAULT_USER_AGENT: &str = concat!("cngateway/", env!("CARGO_PKG_VERSION"));
+
+/// Timeouts and retry policy applied to every gatekeeper call.
+///
+/// Retries only ever apply to read-only GET endpoints (e.g. getactivewatches, ln_listfunds).
+/// Anything that spends or mutates state (batchspend, ln_withdraw, watch...) is sent exactly once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfig {
+    /// Time allowed to establish the tcp + tls connection
+    pub connect_timeout: Duration,
+    /// Time allowed for the whole request, from connect to the last byte of the body
+    pub request_timeout: Duration,
+    /// request_timeout of calls that move funds (spend, bumpfee, batchspend, ln_pay, ln_withdraw, ln_connectfund);
+    /// lightningd keeps trying routes well past a usual request timeout
+    pub spend_timeout: Duration,
+    /// How many times a failed idempotent call is retried
+    pub retries: u32,
+    /// Base delay of the exponential backoff
+    pub backoff: Duration,
+    /// Upper bound of a single backoff delay
+    pub max_backoff: Duration,
+}
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(30),
+            spend_timeout: Duration::from_secs(600),
+            retries: 2,
+            backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+impl ClientConfig {
+    /// Delay before retry number `attempt` (starting at 0): half of the exponential step, plus up to half again of jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let step = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        let half = step / 2;
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(attempt);
+        let jitter = match half.as_millis() as u64 {
+            0 => 0,
+            millis => hasher.finish() % millis,
+        };
+        half + Duration::from_millis(jitter)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CertSource {
+    Path(String),
+    Pem(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum KeySource {
+    File(String),
+    Ring(KeyRing),
+}
+
+/// Configures and builds a CnGateway.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), cngateway::e::S5Error> {
+/// use cngateway::CnGateway;
+/// use std::time::Duration;
+///
+/// let client = CnGateway::builder()
+///     .host("gatekeeper:2009")
+///     .kid("003")
+///     .key("c06f9fc30c50ab7541cefaeb58708fe28babcf7d5ed1767a59685f63d0b63c54")
+///     .cert_path("/path/to/cacert.pem")
+///     .request_timeout(Duration::from_secs(10))
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct CnGatewayBuilder {
+    host: Option<String>,
+    kid: Option<String>,
+    key: Option<String>,
+    keys: Option<KeySource>,
+    cert: Option<CertSource>,
+    scheme: String,
+    base_path: String,
+    user_agent: String,
+    token_lifetime: Duration,
+    config: ClientConfig,
+}
+// the key never shows up in logs or panics
+impl std::fmt::Debug for CnGatewayBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("CnGatewayBuilder")
+            .field("host", &self.host)
+            .field("kid", &self.kid)
+            .field("key", &self.key.as_ref().map(|_| "<redacted>"))
+            .field("keys", &self.keys)
+            .field("cert", &self.cert)
+            .field("scheme", &self.scheme)
+            .field("base_path", &self.base_path)
+            .field("user_agent", &self.user_agent)
+            .field("token_lifetime", &self.token_lifetime)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+impl Default for CnGatewayBuilder {
+    fn default() -> Self {
+        CnGatewayBuilder {
+            host: None,
+            kid: None,
+            key: None,
+            keys: None,
+            cert: None,
+            scheme: DEFAULT_SCHEME.to_string(),
+            base_path: DEFAULT_BASE_PATH.to_string(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            token_lifetime: LIFETIME,
+            config: ClientConfig::default(),
+        }
+    }
+}
+impl CnGatewayBuilder {
+    pub fn new() -> Self {
+        CnGatewayBuilder::default()
+    }
+    /// Read CYPHER_GATEWAY_URL, either CYPHERNODE_KEYS_FILE or CYPHERNODE_API_KID + CYPHERNODE_API_KEY,
+    /// and the optional CYPHERNODE_GATEKEEPER_CERT_CA (pem contents).
+    /// Anything else can still be set on the returned builder.
+    pub fn from_env() -> Result<Self, S5Error> {
+        let builder = CnGatewayBuilder::new().url(required_env(ENV_URL)?)?;
+        let builder = match required_env(ENV_KEYS_FILE) {
+            Ok(path) => builder.keys_file(path),
+            Err(_) => builder
+                .kid(required_env(ENV_KID)?)
+                .key(required_env(ENV_KEY)?),
+        };
+        match std::env::var(ENV_CERT) {
+            Ok(pem) if !pem.trim().is_empty() => Ok(builder.cert_pem(pem)),
+            _ => Ok(builder),
+        }
+    }
+    /// Gatekeeper host and port, eg. gatekeeper:2009
+    pub fn host(mut self, host: impl ToString) -> Self {
+        self.host = Some(host.to_string());
+        self
+    }
+    /// Set scheme, host and base path from a full url, eg. https://localhost:2009/v0/; without a path the base path stays /v0
+    pub fn url(mut self, url: impl ToString) -> Result<Self, S5Error> {
+        let url = match Url::parse(&url.to_string()) {
+            Ok(result) => result,
+            Err(e) => return Err(S5Error::Input(format!("Bad Url: {}", e))),
+        };
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(S5Error::Input("Bad Url: missing host".to_string())),
+        };
+        self.scheme = url.scheme().to_string();
+        self.host = Some(host);
+        // no path keeps the default /v0
+        self.base_path = match url.path().trim_end_matches('/') {
+            "" => DEFAULT_BASE_PATH.to_string(),
+            path => path.to_string(),
+        };
+        Ok(self)
+    }
+    /// Key id from keys.properties, eg. 003
+    pub fn kid(mut self, kid: impl ToString) -> Self {
+        self.kid = Some(kid.to_string());
+        self
+    }
+    /// Hex key matching the kid
+    pub fn key(mut self, key: impl ToString) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+    /// Use every key from a parsed keys.properties; each call picks the least privileged key allowed to make it.
+    /// Takes precedence over kid/key.
+    pub fn keys(mut self, keys: KeyRing) -> Self {
+        self.keys = Some(KeySource::Ring(keys));
+        self
+    }
+    /// Path to a cyphernode keys.properties, read on build
+    pub fn keys_file(mut self, path: impl ToString) -> Self {
+        self.keys = Some(KeySource::File(path.to_string()));
+        self
+    }
+    /// Path to the gatekeeper cacert.pem
+    pub fn cert_path(mut self, path: impl ToString) -> Self {
+        self.cert = Some(CertSource::Path(path.to_string()));
+        self
+    }
+    /// Contents of the gatekeeper cacert.pem
+    pub fn cert_pem(mut self, pem: impl ToString) -> Self {
+        self.cert = Some(CertSource::Pem(pem.to_string()));
+        self
+    }
+    /// Defaults to https
+    pub fn scheme(mut self, scheme: impl ToString) -> Self {
+        self.scheme = scheme.to_string();
+        self
+    }
+    /// Defaults to /v0
+    pub fn base_path(mut self, base_path: impl ToString) -> Self {
+        self.base_path = base_path.to_string();
+        self
+    }
+    /// Defaults to cngateway/<version>
+    pub fn user_agent(mut self, user_agent: impl ToString) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+    /// How long each minted token stays valid. Defaults to 1h.
+    pub fn token_lifetime(mut self, lifetime: Duration) -> Self {
+        self.token_lifetime = lifetime;
+        self
+    }
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = timeout;
+        self
+    }
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.config.request_timeout = timeout;
+        self
+    }
+    pub fn spend_timeout(mut self, timeout: Duration) -> Self {
+        self.config.spend_timeout = timeout;
+        self
+    }
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.config.retries = retries;
+        self
+    }
+    /// Replace all timeouts and the retry policy at once
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+    pub async fn build(self) -> Result<CnGateway, S5Error> {
+        let host = match self.host {
+            Some(host) => host,
+            None => return Err(S5Error::Input("Missing gatekeeper host".to_string())),
+        };
+        let keys = match (self.keys, self.kid, self.key) {
+            (Some(KeySource::Ring(keys)), _, _) => keys,
+            (Some(KeySource::File(path)), _, _) => KeyRing::from_file(path).await?,
+            (None, Some(kid), Some(key)) => KeyRing::single(kid, key),
+            (None, None, _) => return Err(S5Error::Input("Missing api kid".to_string())),
+            (None, _, None) => return Err(S5Error::Input("Missing api key".to_string())),
+        };
+        if keys.keys.is_empty() {
+            return Err(S5Error::Input("Missing api keys".to_string()));
+        }
+        let pem = match self.cert {
+            Some(CertSource::Path(path)) => match tokio::fs::read_to_string(&path).await {
+                Ok(result) => Some(result),
+                Err(e) => return Err(S5Error::Input(format!("Bad Path: {}", e))),
+            },
+            Some(CertSource::Pem(pem)) => Some(pem),
+            None => None,
+        };
+        let cert = match pem.map(|pem| Certificate::from_pem(pem.as_bytes())) {
+            Some(Ok(cert)) => Some(cert),
+            Some(Err(e)) => return Err(S5Error::Tls(e.to_string())),
+            None => None,
+        };
+        // pooled clients, connections are kept alive between calls; calls that move funds get their own timeout
+        let pooled = |timeout: Duration| {
+            let mut builder = Client::builder()
+                .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+                .tcp_keepalive(TCP_KEEPALIVE)
+                .connect_timeout(self.config.connect_timeout)
+                .timeout(timeout)
+                .user_agent(self.user_agent.clone());
+            if let Some(cert) = cert.clone() {
+                builder = builder.add_root_certificate(cert);
+            }
+            match builder.build() {
+                Ok(result) => Ok(result),
+                Err(e) => Err(S5Error::Tls(e.to_string())),
+            }
+        };
+        let client = pooled(self.config.request_timeout)?;
+        let spend_client = pooled(self.config.spend_timeout)?;
+        let base_path = self.base_path.trim_end_matches('/');
+        let base_url = match base_path {
+            "" => format!("{}://{}", self.scheme, host),
+            path if path.starts_with('/') => format!("{}://{}{}", self.scheme, host, path),
+            path => format!("{}://{}/{}", self.scheme, host, path),
+        };
+        let gateway = CnGateway {
+            host,
+            base_url,
+            keys,
+            lifetime: self.token_lifetime,
+            config: self.config,
+            client,
+            spend_client,
+        };
+        Ok(gateway)
+    }
+}
+
+fn required_env(name: &str) -> Result<String, S5Error> {
+    match std::env::var(name) {
+        Ok(value) if !value.trim().is_empty() => Ok(value.trim().to_string()),
+        _ => Err(S5Error::Input(format!("Missing env {}", name))),
+    }
+}
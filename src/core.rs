@@ -1,10 +1,44 @@
-use crate::e::{ErrorKind, S5Error};
-use reqwest::{
-    self,
-    header::{HeaderMap, AUTHORIZATION},
-    Certificate,
-};
+use crate::e::S5Error;
+use crate::http;
+use crate::routes;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// bitcoind rpc responses forwarded as is by the proxy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcResponse<T> {
+    pub result: Option<T>,
+    pub error: Option<Value>,
+    pub id: Option<Value>,
+}
+impl<T: DeserializeOwned> RpcResponse<T> {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<RpcResponse<T>, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+    /// The result, or the rpc error message
+    pub fn into_result(self, stringified: &str) -> Result<T, S5Error> {
+        match (self.result, self.error) {
+            (Some(result), _) => Ok(result),
+            (None, Some(error)) if !error.is_null() => match error.get("message") {
+                Some(Value::String(message)) => Err(S5Error::Gatekeeper(message.to_string())),
+                _ => Err(S5Error::Gatekeeper(error.to_string())),
+            },
+            (None, _) => Err(S5Error::Deserialize {
+                message: "missing result".to_string(),
+                body: stringified.to_string(),
+            }),
+        }
+    }
+}
 
 
 // POST http://cyphernode:8888/getnewaddress
@@ -14,23 +48,19 @@ RESPONSE{
   "balance":1.51911837
 }
 */
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AddressType {
+  #[default]
   Bech32,
   P2SH,
   Legacy
 }
-impl Default for AddressType{
-  fn default()->Self{
-    AddressType::Bech32
-  }
-}
-impl ToString for AddressType{
-  fn to_string(&self)->String{
+impl Display for AddressType{
+  fn fmt(&self, f: &mut Formatter) -> std::fmt::Result{
       match self{
-        AddressType::Bech32=>"bech32".to_string(),
-        AddressType::P2SH=>"p2sh-segwit".to_string(),
-        AddressType::Legacy=>"legacy".to_string(),
+        AddressType::Bech32=>write!(f, "bech32"),
+        AddressType::P2SH=>write!(f, "p2sh-segwit"),
+        AddressType::Legacy=>write!(f, "legacy"),
       }
   }
 
@@ -42,10 +72,11 @@ pub struct AddressRequest {
 }
 impl AddressRequest {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<AddressRequest, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(ErrorKind::Internal, &e.to_string())),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -56,42 +87,24 @@ pub struct Address {
 }
 impl Address {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<Address, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(ErrorKind::Internal, &e.to_string())),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 ///Returns the a new address from core wallet
 pub async fn getnewaddress(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: AddressRequest,
-) -> Result<Address, String> {
-    let full_url: String = format!("https://{}/v0/getnewaddress", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match Address::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+) -> Result<Address, S5Error> {
+    let full_url: String = routes::GETNEWADDRESS.url(&base_url);
+    let text = http::post(client, &routes::GETNEWADDRESS, &full_url, jwt, &body).await?;
+    Address::from_str(&text)
 }
 
 // GET http://cyphernode:8888/getmempoolinfo
@@ -116,41 +129,23 @@ pub struct MempoolInfo {
 }
 impl MempoolInfo {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<MempoolInfo, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(ErrorKind::Internal, &e.to_string())),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 ///Returns the mempool information of the Bitcoin node.
 pub async fn getmempoolinfo(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<MempoolInfo, String> {
-    let full_url: String = format!("https://{}/v0/getmempoolinfo", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match MempoolInfo::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+    client: &Client,
+) -> Result<MempoolInfo, S5Error> {
+    let full_url: String = routes::GETMEMPOOLINFO.url(&base_url);
+    let text = http::get(client, &routes::GETMEMPOOLINFO, &full_url, jwt).await?;
+    MempoolInfo::from_str(&text)
 }
 
 // GET http://cyphernode:8888/getblockchaininfo
@@ -208,6 +203,332 @@ RESPONSE{
 }
 */
 
+/// Chain state of the bitcoin node.
+/// softforks is a list (with bip9_softforks alongside) up to bitcoind 0.18 and a map by name since 0.19.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockchainInfo {
+    pub chain: String,
+    pub blocks: u64,
+    pub headers: u64,
+    pub bestblockhash: String,
+    pub difficulty: f64,
+    pub mediantime: u64,
+    /// 0..1
+    pub verificationprogress: f64,
+    pub initialblockdownload: bool,
+    pub chainwork: String,
+    pub size_on_disk: u64,
+    pub pruned: bool,
+    #[serde(default)]
+    pub softforks: Softforks,
+    #[serde(default)]
+    pub bip9_softforks: BTreeMap<String, Bip9>,
+    #[serde(default)]
+    pub warnings: Value,
+}
+impl BlockchainInfo {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<BlockchainInfo, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Softforks {
+    /// bitcoind >= 0.19: bip34, csv, segwit, taproot...
+    ByName(BTreeMap<String, Softfork>),
+    /// bitcoind <= 0.18: version based forks only
+    Legacy(Vec<LegacySoftfork>),
+}
+impl Default for Softforks {
+    fn default() -> Self {
+        Softforks::ByName(BTreeMap::new())
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Softfork {
+    /// buried or bip9
+    #[serde(rename = "type")]
+    pub type_field: String,
+    pub active: bool,
+    pub height: Option<u64>,
+    pub bip9: Option<Bip9>,
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LegacySoftfork {
+    pub id: String,
+    pub version: i64,
+    pub reject: LegacyReject,
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LegacyReject {
+    pub status: bool,
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bip9 {
+    pub status: Bip9Status,
+    #[serde(alias = "startTime")]
+    pub start_time: i64,
+    pub timeout: i64,
+    pub since: u64,
+    pub min_activation_height: Option<u64>,
+}
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bip9Status {
+    #[default]
+    Defined,
+    Started,
+    LockedIn,
+    Active,
+    Failed,
+}
+///Returns the chain state of the Bitcoin node: tip, verification progress and softfork deployments.
+pub async fn getblockchaininfo(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+) -> Result<BlockchainInfo, S5Error> {
+    let full_url: String = routes::GETBLOCKCHAININFO.url(&base_url);
+    let text = http::get(client, &routes::GETBLOCKCHAININFO, &full_url, jwt).await?;
+    BlockchainInfo::from_str(&text)
+}
+
+// GET http://cyphernode:8888/getblockhash/593104
+// GET http://cyphernode:8888/getbestblockhash
+/*
+RESPONSE{
+  "result":"00000000a5fb2ae3c4dc17a4eb74e22b5a8ab8c9b1f1bb4b4f1d53e3f4a6c1a3",
+  "error":null,
+  "id":null
+}
+*/
+pub type BlockHashResponse = RpcResponse<String>;
+///Returns the hash of the block at height in the best chain.
+pub async fn getblockhash(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    height: u64,
+) -> Result<String, S5Error> {
+    let full_url: String = routes::GETBLOCKHASH.url_with(&base_url, &[&height.to_string()]);
+    let text = http::get(client, &routes::GETBLOCKHASH, &full_url, jwt).await?;
+    BlockHashResponse::from_str(&text)?.into_result(&text)
+}
+///Returns the hash of the tip of the best chain.
+pub async fn getbestblockhash(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+) -> Result<String, S5Error> {
+    let full_url: String = routes::GETBESTBLOCKHASH.url(&base_url);
+    let text = http::get(client, &routes::GETBESTBLOCKHASH, &full_url, jwt).await?;
+    BlockHashResponse::from_str(&text)?.into_result(&text)
+}
+
+// GET http://cyphernode:8888/getblockinfo/000000006f82a384c208ecfa04d05beea02d420f3f398ddda5c7f900de5718ea
+// GET http://cyphernode:8888/getbestblockinfo
+/*
+RESPONSE{
+  "result":{
+    "hash":"000000006f82a384c208ecfa04d05beea02d420f3f398ddda5c7f900de5718ea",
+    "confirmations":1,
+    "strippedsize":269,
+    "size":305,
+    "weight":1112,
+    "height":1486869,
+    "version":536870912,
+    "versionHex":"20000000",
+    "merkleroot":"4f9de0a9f8a12d9ba5e7e46c5d1a8e6d0b8f2f5c3c81a1b5b5d8e2f2c1a2b3c4",
+    "tx":["4f9de0a9f8a12d9ba5e7e46c5d1a8e6d0b8f2f5c3c81a1b5b5d8e2f2c1a2b3c4"],
+    "time":1554151563,
+    "mediantime":1554149114,
+    "nonce":3187548412,
+    "bits":"1d00ffff",
+    "difficulty":1,
+    "chainwork":"000000000000000000000000000000000000000000000103ceb57a5896f347ce",
+    "nTx":1,
+    "previousblockhash":"000000000000002fb99d683e64bbfc2b7ad16f9a425cf7be77b481fb1afa363b"
+  },
+  "error":null,
+  "id":null
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockInfo {
+    pub hash: String,
+    /// -1 when the block is not on the best chain
+    pub confirmations: i64,
+    pub strippedsize: u64,
+    pub size: u64,
+    pub weight: u64,
+    pub height: u64,
+    pub version: i64,
+    #[serde(rename = "versionHex")]
+    pub version_hex: String,
+    pub merkleroot: String,
+    pub tx: Vec<String>,
+    pub time: u64,
+    pub mediantime: u64,
+    pub nonce: u64,
+    pub bits: String,
+    pub difficulty: f64,
+    pub chainwork: String,
+    #[serde(rename = "nTx")]
+    pub n_tx: u64,
+    pub previousblockhash: Option<String>,
+    pub nextblockhash: Option<String>,
+}
+pub type BlockInfoResponse = RpcResponse<BlockInfo>;
+///Returns the block header fields and txids of a block.
+pub async fn getblockinfo(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    blockhash: String,
+) -> Result<BlockInfo, S5Error> {
+    let full_url: String = routes::GETBLOCKINFO.url_with(&base_url, &[&blockhash]);
+    let text = http::get(client, &routes::GETBLOCKINFO, &full_url, jwt).await?;
+    BlockInfoResponse::from_str(&text)?.into_result(&text)
+}
+///Returns the tip of the best chain.
+pub async fn getbestblockinfo(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+) -> Result<BlockInfo, S5Error> {
+    let full_url: String = routes::GETBESTBLOCKINFO.url(&base_url);
+    let text = http::get(client, &routes::GETBESTBLOCKINFO, &full_url, jwt).await?;
+    BlockInfoResponse::from_str(&text)?.into_result(&text)
+}
+
+// GET http://cyphernode:8888/gettransaction/af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648
+/*
+RESPONSE{
+  "result":{
+    "amount":-0.0001,
+    "fee":-0.00000141,
+    "confirmations":12,
+    "blockhash":"000000000000001f7c6ec7ad3e3ae3d1b0e8b4b1a1f3f8ad44d2c7b6b3c5a1e2",
+    "blockheight":2403000,
+    "blockindex":7,
+    "blocktime":1670221519,
+    "txid":"af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648",
+    "walletconflicts":[],
+    "time":1670221000,
+    "timereceived":1670221000,
+    "bip125-replaceable":"no",
+    "details":[
+      {
+        "address":"tb1qmqwrk6q5u6v3yq0ssqd9xqqkxq4nvmu0rg5wrz",
+        "category":"send",
+        "amount":-0.0001,
+        "label":"",
+        "vout":1,
+        "fee":-0.00000141,
+        "abandoned":false
+      }
+    ],
+    "hex":"02000000000101..."
+  },
+  "error":null,
+  "id":null
+}
+*/
+/// A transaction of the spending wallet
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletTransaction {
+    /// Net amount in BTC, negative when sending
+    pub amount: f64,
+    /// BTC, negative; only set when the wallet paid it
+    pub fee: Option<f64>,
+    /// -1 when conflicted
+    pub confirmations: i64,
+    pub blockhash: Option<String>,
+    pub blockheight: Option<u64>,
+    pub blockindex: Option<u64>,
+    pub blocktime: Option<u64>,
+    pub txid: String,
+    #[serde(default)]
+    pub walletconflicts: Vec<String>,
+    pub time: u64,
+    pub timereceived: u64,
+    /// yes, no or unknown
+    #[serde(rename = "bip125-replaceable", default)]
+    pub bip125_replaceable: String,
+    #[serde(default)]
+    pub details: Vec<TransactionDetail>,
+    pub hex: String,
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionDetail {
+    pub address: Option<String>,
+    pub category: TransactionCategory,
+    pub amount: f64,
+    pub label: Option<String>,
+    pub vout: u32,
+    pub fee: Option<f64>,
+    pub abandoned: Option<bool>,
+}
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionCategory {
+    Send,
+    #[default]
+    Receive,
+    Generate,
+    Immature,
+    Orphan,
+}
+pub type WalletTransactionResponse = RpcResponse<WalletTransaction>;
+///Returns a wallet transaction by txid.
+pub async fn gettransaction(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    txid: String,
+) -> Result<WalletTransaction, S5Error> {
+    let full_url: String = routes::GETTRANSACTION.url_with(&base_url, &[&txid]);
+    let text = http::get(client, &routes::GETTRANSACTION, &full_url, jwt).await?;
+    WalletTransactionResponse::from_str(&text)?.into_result(&text)
+}
+
+// GET http://cyphernode:8888/conf/b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387
+/*
+RESPONSE{
+  "result":"confirmed"
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Confirmation {
+    pub result: String,
+}
+impl Confirmation {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<Confirmation, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+///Processes a wallet transaction as bitcoind's walletnotify would: updates watches and fires their callbacks.
+pub async fn conf(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    txid: String,
+) -> Result<Confirmation, S5Error> {
+    let full_url: String = routes::CONF.url_with(&base_url, &[&txid]);
+    let text = http::get(client, &routes::CONF, &full_url, jwt).await?;
+    Confirmation::from_str(&text)
+}
+
 // GET http://cyphernode:8888/getbalance
 /*
 RESPONSE{
@@ -220,41 +541,105 @@ pub struct Balance {
 }
 impl Balance {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<Balance, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(ErrorKind::Internal, &e.to_string())),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 ///Returns the balance of core wallet
 pub async fn getbalance(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<Balance, String> {
-    let full_url: String = format!("https://{}/v0/getbalance", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
+    client: &Client,
+) -> Result<Balance, S5Error> {
+    let full_url: String = routes::GETBALANCE.url(&base_url);
+    let text = http::get(client, &routes::GETBALANCE, &full_url, jwt).await?;
+    Balance::from_str(&text)
+}
 
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                match Balance::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
+// GET http://cyphernode:8888/getbalances
+/*
+RESPONSE{
+  "balances":{
+    "mine":{
+      "trusted":1.29979716,
+      "untrusted_pending":0,
+      "immature":0
+    }
+  }
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Balances {
+    pub balances: WalletBalances,
+}
+impl Balances {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<Balances, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
     }
 }
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletBalances {
+    pub mine: BalanceBreakdown,
+}
+/// BTC
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceBreakdown {
+    /// Confirmed, or unconfirmed from the wallet itself
+    pub trusted: f64,
+    /// Unconfirmed from others
+    pub untrusted_pending: f64,
+    /// Coinbase outputs not yet mature
+    pub immature: f64,
+}
+///Returns the balance of core wallet by trust level
+pub async fn getbalances(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+) -> Result<Balances, S5Error> {
+    let full_url: String = routes::GETBALANCES.url(&base_url);
+    let text = http::get(client, &routes::GETBALANCES, &full_url, jwt).await?;
+    Balances::from_str(&text)
+}
+
+// GET http://cyphernode:8888/getbalancebyxpub/upub5GtUcgGed1aGH4HKQ3vMYrsmLXwmHhS1AeX33ZvDgZiyvkGhNTvGd2TA5Lr4v239Fzjj4ZY48t6wTtXUy2yRgapf37QHgt6KWEZ6bgsCLpb
+// GET http://cyphernode:8888/getbalancebyxpublabel/2219
+/*
+RESPONSE{
+  "balance":0.00316
+}
+*/
+///Returns the confirmed balance of the addresses derived from a watched xpub
+pub async fn getbalancebyxpub(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    xpub: String,
+) -> Result<Balance, S5Error> {
+    let full_url: String = routes::GETBALANCEBYXPUB.url_with(&base_url, &[&xpub]);
+    let text = http::get(client, &routes::GETBALANCEBYXPUB, &full_url, jwt).await?;
+    Balance::from_str(&text)
+}
+///Returns the confirmed balance of the addresses derived from the xpub watched under label
+pub async fn getbalancebyxpublabel(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    label: String,
+) -> Result<Balance, S5Error> {
+    let full_url: String = routes::GETBALANCEBYXPUBLABEL.url_with(&base_url, &[&label]);
+    let text = http::get(client, &routes::GETBALANCEBYXPUBLABEL, &full_url, jwt).await?;
+    Balance::from_str(&text)
+}
 
 
 
@@ -266,13 +651,11 @@ pub struct ValidateAddressResponse {
     pub id: Option<usize>
 }
 impl ValidateAddressResponse {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<ValidateAddressResponse, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -282,53 +665,183 @@ pub struct ValidatedAddress {
     pub isvalid: bool,
 }
 impl ValidatedAddress {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<ValidatedAddress, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 
 pub async fn validateaddress(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     address: String
-) -> Result<bool, String> {
-    let full_url: String = format!("https://{}/v0/validateaddress/{}", host,address).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
+) -> Result<bool, S5Error> {
+    let full_url: String = routes::VALIDATEADDRESS.url_with(&base_url, &[&address]);
+    let text = http::get(client, &routes::VALIDATEADDRESS, &full_url, jwt).await?;
+    let val = ValidateAddressResponse::from_str(&text)?;
+    match (val.result, val.error) {
+        (Some(result), _) => Ok(result.isvalid),
+        (None, Some(error)) => Err(S5Error::Gatekeeper(error)),
+        (None, None) => Err(S5Error::Deserialize {
+            message: "missing result".to_string(),
+            body: text,
+        }),
+    }
+}
 
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match ValidateAddressResponse::from_str(&text) {
-                    Ok(val) => {
-                        if val.result.is_some(){
-                            Ok(val.result.unwrap().isvalid)
-                        }
-                        else{
-                            return Err(val.error.unwrap())
-                        }
-                    },
-                    Err(e) => return Err(e.message),
-                }
-            }
-            Err(e) => return Err(e.to_string()),
-        },
-        Err(e) => return Err(e.to_string()),
+// POST http://cyphernode:8888/spend
+/*
+REQUEST{
+  "address":"tb1qmqwrk6q5u6v3yq0ssqd9xqqkxq4nvmu0rg5wrz",
+  "amount":0.00233,
+  "confTarget":6,
+  "replaceable":true,
+  "subtractfeefromamount":false
+}
+RESPONSE{
+  "status":"accepted",
+  "hash":"af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648",
+  "details":{
+    "address":"tb1qmqwrk6q5u6v3yq0ssqd9xqqkxq4nvmu0rg5wrz",
+    "amount":0.00233,
+    "firstseen":1584568841,
+    "size":222,
+    "vsize":141,
+    "replaceable":true,
+    "fee":0.00000141,
+    "subtractfeefromamount":false
+  }
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendReq {
+    pub address: String,
+    /// BTC
+    pub amount: f64,
+    #[serde(rename = "confTarget", skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtractfeefromamount: Option<bool>,
+}
+impl SpendReq {
+    /// Options left as None use the spending wallet defaults
+    pub fn new(
+        address: String,
+        amount: f64,
+        conf_target: Option<u32>,
+        replaceable: Option<bool>,
+        subtractfeefromamount: Option<bool>,
+    ) -> Self {
+        SpendReq {
+            address,
+            amount,
+            conf_target,
+            replaceable,
+            subtractfeefromamount,
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spend {
+    /// accepted, or rejected along with a message
+    pub status: String,
+    #[serde(rename = "hash", default)]
+    pub txid: String,
+    pub details: Option<SpendDetails>,
+    pub message: Option<String>,
+}
+impl Spend {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<Spend, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendDetails {
+    pub address: String,
+    pub amount: f64,
+    pub firstseen: u64,
+    pub size: u64,
+    pub vsize: u64,
+    pub replaceable: bool,
+    /// BTC
+    pub fee: f64,
+    pub subtractfeefromamount: bool,
+}
+///Sends amount to address from the spending wallet. A rejected spend is returned as an error.
+pub async fn spend(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    body: SpendReq,
+) -> Result<Spend, S5Error> {
+    let full_url: String = routes::SPEND.url(&base_url);
+    let text = http::post(client, &routes::SPEND, &full_url, jwt, &body).await?;
+    let spend = Spend::from_str(&text)?;
+    if spend.status != "accepted" {
+        return Err(S5Error::Gatekeeper(spend.message.unwrap_or_else(|| "spend rejected".to_string())));
     }
+    Ok(spend)
+}
 
+// POST http://cyphernode:8888/bumpfee
+/*
+REQUEST{
+  "txid":"af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648",
+  "confTarget":4
+}
+RESPONSE{
+  "result":{
+    "txid":"3e87fb4ecd6ea6f2bd8e4b8ba8e6e1d3bb5b8a7d64c0dc2e4eb1d5c0e5a8cb14",
+    "origfee":0.00000141,
+    "fee":0.00000282,
+    "errors":[]
+  },
+  "error":null,
+  "id":null
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BumpFeeReq {
+    pub txid: String,
+    #[serde(rename = "confTarget", skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u32>,
+}
+impl BumpFeeReq {
+    pub fn new(txid: String, conf_target: Option<u32>) -> Self {
+        BumpFeeReq { txid, conf_target }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BumpFee {
+    /// The replacement transaction
+    pub txid: String,
+    pub origfee: f64,
+    pub fee: f64,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+pub type BumpFeeResponse = RpcResponse<BumpFee>;
+///Replaces an unconfirmed replaceable wallet transaction with a higher fee one.
+pub async fn bumpfee(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    body: BumpFeeReq,
+) -> Result<BumpFee, S5Error> {
+    let full_url: String = routes::BUMPFEE.url(&base_url);
+    let text = http::post(client, &routes::BUMPFEE, &full_url, jwt, &body).await?;
+    BumpFeeResponse::from_str(&text)?.into_result(&text)
 }
 
 // POST http://cyphernode:8888/bitcoin_estimatesmartfee
@@ -345,3 +858,56 @@ RESPONSE{
     "id": null
 }
 */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EstimateMode {
+    Unset,
+    Economical,
+    Conservative,
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EstimateSmartFeeReq {
+    #[serde(rename = "confTarget")]
+    pub conf_target: u32,
+    /// bitcoind defaults to conservative
+    #[serde(rename = "estimateMode", skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+}
+impl EstimateSmartFeeReq {
+    pub fn new(conf_target: u32, estimate_mode: Option<EstimateMode>) -> Self {
+        EstimateSmartFeeReq {
+            conf_target,
+            estimate_mode,
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmartFee {
+    /// BTC/kvB; missing when the node does not have enough data yet
+    pub feerate: Option<f64>,
+    /// Block target the estimate is actually for
+    pub blocks: u32,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+impl SmartFee {
+    pub fn btc_per_kvb(&self) -> Option<f64> {
+        self.feerate
+    }
+    /// 1 BTC/kvB = 100000 sat/vB
+    pub fn sat_per_vb(&self) -> Option<f64> {
+        self.feerate.map(|feerate| feerate * 100_000.0)
+    }
+}
+pub type SmartFeeResponse = RpcResponse<SmartFee>;
+///Estimates the feerate for a transaction to confirm within conf_target blocks.
+pub async fn estimatesmartfee(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    body: EstimateSmartFeeReq,
+) -> Result<SmartFee, S5Error> {
+    let full_url: String = routes::BITCOIN_ESTIMATESMARTFEE.url(&base_url);
+    let text = http::post(client, &routes::BITCOIN_ESTIMATESMARTFEE, &full_url, jwt, &body).await?;
+    SmartFeeResponse::from_str(&text)?.into_result(&text)
+}
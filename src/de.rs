@@ -0,0 +1,87 @@
+// cyphernode sends many numbers as strings, and "" where there is no value yet.
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString<T> {
+    Number(T),
+    String(String),
+}
+
+/// Accept 121 or "121"; "" and null become None
+pub(crate) fn option_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    match Option::<NumberOrString<T>>::deserialize(deserializer)? {
+        Some(NumberOrString::Number(value)) => Ok(Some(value)),
+        Some(NumberOrString::String(value)) if value.trim().is_empty() => Ok(None),
+        Some(NumberOrString::String(value)) => match value.trim().parse::<T>() {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => Err(serde::de::Error::custom(e)),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Accept 121 or "121"
+pub(crate) fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    match option_number(deserializer)? {
+        Some(value) => Ok(value),
+        None => Err(serde::de::Error::custom("expected a number, got an empty value")),
+    }
+}
+
+/// Accept 1000, "1000" or "1000msat" (lightningd amounts); null becomes None
+pub(crate) fn option_msat<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<NumberOrString<u64>>::deserialize(deserializer)? {
+        Some(NumberOrString::Number(value)) => Ok(Some(value)),
+        Some(NumberOrString::String(value)) => match value.trim().trim_end_matches("msat").parse::<u64>() {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => Err(serde::de::Error::custom(e)),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Accept 1000, "1000" or "1000msat"
+pub(crate) fn msat<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match option_msat(deserializer)? {
+        Some(value) => Ok(value),
+        None => Err(serde::de::Error::custom("expected an msat amount, got null")),
+    }
+}
+
+/// Accept true, 1 or "1"
+pub(crate) fn flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Bool(value) => Ok(value),
+        Value::Number(value) => Ok(value.as_u64() != Some(0)),
+        Value::String(value) => match value.trim() {
+            "1" | "true" => Ok(true),
+            "0" | "false" | "" => Ok(false),
+            other => Err(serde::de::Error::custom(format!("expected a flag, got {}", other))),
+        },
+        Value::Null => Ok(false),
+        other => Err(serde::de::Error::custom(format!("expected a flag, got {}", other))),
+    }
+}
@@ -34,37 +34,118 @@ impl PartialEq for ErrorKind {
   }
 }
 
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct S5Error {
-  pub kind: String,
-  pub message: String,
+/// Every error returned by the gatekeeper client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum S5Error {
+  /// The request never reached the gatekeeper: dns, connection refused, connect timeout.
+  Transport(String),
+  /// The request may have reached cyphernode but no complete response came back: timeout, dropped connection.
+  /// Whether the call took effect is unknown.
+  Interrupted(String),
+  /// The root certificate could not be loaded or the client could not be built with it.
+  Tls(String),
+  /// The auth token could not be created.
+  Auth(String),
+  /// 401: the token was rejected; wrong kid/key or expired.
+  Unauthorized(String),
+  /// 403: the key's group is not allowed to call this endpoint.
+  Forbidden(String),
+  /// 404: unknown endpoint or resource.
+  NotFound(String),
+  /// 502/503/504: the gatekeeper is up but the proxy behind it is not.
+  Unavailable { status: u16, body: String },
+  /// Any other non-success status.
+  Http { status: u16, body: String },
+  /// The gatekeeper answered but reported an `error` in the response.
+  Gatekeeper(String),
+  /// The response body could not be converted into the expected type.
+  /// `body` is kept for inspection but left out of `Display`.
+  Deserialize { message: String, body: String },
+  /// Bad input supplied by the caller.
+  Input(String),
+  /// Anything else; a bug in this client.
+  Internal(String),
 }
 
 impl S5Error {
-  pub fn new(kind: ErrorKind, message: &str) -> Self {
-    S5Error {
-      kind: kind.to_string(),
-      message: message.to_string(),
+  /// Used internally to attach the raw response body to a decode failure
+  pub fn deserialize(e: serde_json::Error, body: &str) -> Self {
+    S5Error::Deserialize {
+      message: e.to_string(),
+      body: body.to_string(),
+    }
+  }
+  /// Used internally to map a non-success status and its body
+  pub fn from_status(status: u16, body: String) -> Self {
+    match status {
+      401 => S5Error::Unauthorized(body),
+      403 => S5Error::Forbidden(body),
+      404 => S5Error::NotFound(body),
+      502..=504 => S5Error::Unavailable { status, body },
+      _ => S5Error::Http { status, body },
+    }
+  }
+  /// Safe to resend: the request never reached the gatekeeper, or the proxy was down (502/503) and never forwarded it.
+  ///
+  /// Timeouts, dropped connections ([`S5Error::Interrupted`]) and 504s are not transient: cyphernode may have
+  /// processed the call. Never blindly retry a call that moves funds (spend, bumpfee, batchspend, ln_pay,
+  /// ln_withdraw, ln_connectfund); check its outcome first, eg. with gettransaction or ln_listpays.
+  pub fn is_transient(&self) -> bool {
+    matches!(self, S5Error::Transport(_) | S5Error::Unavailable { status: 502 | 503, .. })
+  }
+  /// Coarse classification of the error
+  pub fn kind(&self) -> ErrorKind {
+    match self {
+      S5Error::Transport(_) | S5Error::Interrupted(_) | S5Error::Tls(_) => ErrorKind::Network,
+      S5Error::Auth(_) | S5Error::Unauthorized(_) | S5Error::Forbidden(_) => ErrorKind::Key,
+      S5Error::NotFound(_) => ErrorKind::NoResource,
+      S5Error::Unavailable { .. } => ErrorKind::Network,
+      S5Error::Http { status, .. } => match status {
+        400 | 409 => ErrorKind::Input,
+        _ => ErrorKind::Internal,
+      },
+      S5Error::Gatekeeper(_) => ErrorKind::Wallet,
+      S5Error::Deserialize { .. } => ErrorKind::Internal,
+      S5Error::Input(_) => ErrorKind::Input,
+      S5Error::Internal(_) => ErrorKind::Internal,
+    }
+  }
+}
+
+impl Display for S5Error {
+  fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    match self {
+      S5Error::Transport(message) => write!(f, "Transport Error: {}", message),
+      S5Error::Interrupted(message) => write!(f, "Interrupted: {}", message),
+      S5Error::Tls(message) => write!(f, "TLS Error: {}", message),
+      S5Error::Auth(message) => write!(f, "Auth Error: {}", message),
+      S5Error::Unauthorized(body) => write!(f, "Unauthorized: {}", body),
+      S5Error::Forbidden(body) => write!(f, "Forbidden: {}", body),
+      S5Error::NotFound(body) => write!(f, "Not Found: {}", body),
+      S5Error::Unavailable { status, body } => write!(f, "Upstream Unavailable ({}): {}", status, body),
+      S5Error::Http { status, body } => write!(f, "HTTP {}: {}", status, body),
+      S5Error::Gatekeeper(message) => write!(f, "Gatekeeper Error: {}", message),
+      // the body holds addresses, invoices and balances: keep it on the variant, out of logs
+      S5Error::Deserialize { message, body } => {
+        write!(f, "Deserialize Error: {} ({} byte body)", message, body.len())
+      }
+      S5Error::Input(message) => write!(f, "Input Error: {}", message),
+      S5Error::Internal(message) => write!(f, "Internal Error: {}", message),
     }
   }
-  // pub fn from_ureq(e: ureq::Error)->Self{
-  //   match e {
-  //     ureq::Error::Status(code, response) => {
-  //       let kind = match code {
-  //           400 => ErrorKind::Input,
-  //           401 => ErrorKind::Key,
-  //           403 => ErrorKind::Key,
-  //           404 => ErrorKind::NoResource,
-  //           409 => ErrorKind::Input,
-  //           _=> ErrorKind::Internal
-  //       };
-  //       S5Error::new(kind, &response.into_string().unwrap())
-  //     }
-  //     _ => { 
-  //       S5Error::new(ErrorKind::Network, "Transport Error. Check your internet connection AND/OR your request object.")
-  //     }
-  //   }
-  // }
+}
 
-}
\ No newline at end of file
+impl std::error::Error for S5Error {}
+
+impl From<reqwest::Error> for S5Error {
+  fn from(e: reqwest::Error) -> Self {
+    if e.is_builder() {
+      S5Error::Tls(e.to_string())
+    } else if e.is_connect() {
+      S5Error::Transport(e.to_string())
+    } else {
+      // the request may already be with cyphernode
+      S5Error::Interrupted(e.to_string())
+    }
+  }
+}
@@ -0,0 +1,164 @@
+// Single place where requests hit the gatekeeper.
+use crate::e::S5Error;
+use crate::routes::{Method, Route};
+use reqwest::{Client, RequestBuilder};
+use serde::Serialize;
+
+/// GET the full_url of a GET route and return the raw response body
+pub(crate) async fn get(
+    client: &Client,
+    route: &Route,
+    full_url: &str,
+    jwt: String,
+) -> Result<String, S5Error> {
+    debug_assert_eq!(route.method, Method::Get, "{} is not a GET route", route.path);
+    send("GET", route.path, full_url, client.get(full_url).bearer_auth(jwt)).await
+}
+
+/// POST body as json to the full_url of a POST route and return the raw response body
+pub(crate) async fn post<T: Serialize + ?Sized>(
+    client: &Client,
+    route: &Route,
+    full_url: &str,
+    jwt: String,
+    body: &T,
+) -> Result<String, S5Error> {
+    debug_assert_eq!(route.method, Method::Post, "{} is not a POST route", route.path);
+    send(
+        "POST",
+        route.path,
+        full_url,
+        client.post(full_url).bearer_auth(jwt).json(body),
+    )
+    .await
+}
+
+#[cfg(not(feature = "tracing"))]
+async fn send(
+    _method: &'static str,
+    _endpoint: &'static str,
+    _full_url: &str,
+    request: RequestBuilder,
+) -> Result<String, S5Error> {
+    exchange(request).await
+}
+
+#[cfg(feature = "tracing")]
+async fn send(
+    method: &'static str,
+    endpoint: &'static str,
+    full_url: &str,
+    request: RequestBuilder,
+) -> Result<String, S5Error> {
+    use tracing::Instrument;
+    let span = tracing::info_span!(
+        "cngateway",
+        method,
+        endpoint,
+        path = %trace::path(full_url, endpoint),
+        status = tracing::field::Empty,
+        latency_ms = tracing::field::Empty,
+    );
+    exchange(request).instrument(span).await
+}
+
+async fn exchange(request: RequestBuilder) -> Result<String, S5Error> {
+    #[cfg(feature = "tracing")]
+    let start = std::time::Instant::now();
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = %e, "gatekeeper unreachable");
+            return Err(e.into());
+        }
+    };
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+        span.record("status", response.status().as_u16());
+        span.record("latency_ms", start.elapsed().as_millis() as u64);
+    }
+    let status = response.status();
+    let text = response.text().await?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(body = %trace::redact(&text), "gatekeeper response");
+    if status.is_success() {
+        Ok(text)
+    } else {
+        Err(S5Error::from_status(status.as_u16(), text))
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod trace {
+    use serde_json::Value;
+
+    const REDACTED: &str = "[REDACTED]";
+    /// Response fields that must never reach the logs
+    const SENSITIVE: &[&str] = &[
+        "address",
+        "bech32",
+        "destination",
+        "pub32",
+        "bolt11",
+        "payment_hash",
+        "preimage",
+        "payment_preimage",
+        "payment_secret",
+        "connectstring",
+        "tx",
+        "hex",
+        "label",
+        "balance",
+        "amount",
+        "total",
+        "value",
+        "sent_amount",
+        "amount_msat",
+        "amount_sent_msat",
+        "msatoshi",
+    ];
+
+    /// Path of the url up to the endpoint name; path parameters (addresses, invoices, xpubs) are dropped
+    pub(super) fn path(full_url: &str, endpoint: &str) -> String {
+        let path = match full_url.find("://") {
+            Some(i) => match full_url[i + 3..].find('/') {
+                Some(j) => &full_url[i + 3 + j..],
+                None => "/",
+            },
+            None => full_url,
+        };
+        match path.find(&format!("/{}/", endpoint)) {
+            Some(i) => format!("{}/{}/{}", &path[..i], endpoint, REDACTED),
+            None => path.to_string(),
+        }
+    }
+
+    /// Replace the value of every sensitive field in a json body
+    pub(super) fn redact(body: &str) -> String {
+        match serde_json::from_str::<Value>(body) {
+            Ok(mut value) => {
+                scrub(&mut value);
+                value.to_string()
+            }
+            Err(_) => format!("<{} bytes of non-json>", body.len()),
+        }
+    }
+
+    fn scrub(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if SENSITIVE.contains(&key.as_str()) {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        scrub(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(scrub),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::e::S5Error;
+use crate::routes;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+// cyphernode/gatekeeper/keys.properties
+/*
+kapi_id="000";kapi_key="<64 hex chars>";kapi_groups="stats";eval ugroups_${kapi_id}=${kapi_groups};eval ukey_${kapi_id}=${kapi_key}
+kapi_id="001";kapi_key="<64 hex chars>";kapi_groups="stats,watcher";eval ugroups_${kapi_id}=${kapi_groups};eval ukey_${kapi_id}=${kapi_key}
+kapi_id="002";kapi_key="<64 hex chars>";kapi_groups="stats,watcher,spender";eval ugroups_${kapi_id}=${kapi_groups};eval ukey_${kapi_id}=${kapi_key}
+kapi_id="003";kapi_key="<64 hex chars>";kapi_groups="stats,watcher,spender,admin";eval ugroups_${kapi_id}=${kapi_groups};eval ukey_${kapi_id}=${kapi_key}
+*/
+
+/// Gatekeeper api groups, from least to most privileged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ApiGroup {
+    Stats,
+    Watcher,
+    Spender,
+    Admin,
+}
+impl ApiGroup {
+    pub fn all() -> Vec<ApiGroup> {
+        vec![
+            ApiGroup::Stats,
+            ApiGroup::Watcher,
+            ApiGroup::Spender,
+            ApiGroup::Admin,
+        ]
+    }
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(group: &str) -> Result<ApiGroup, S5Error> {
+        match group.trim() {
+            "stats" => Ok(ApiGroup::Stats),
+            "watcher" => Ok(ApiGroup::Watcher),
+            "spender" => Ok(ApiGroup::Spender),
+            "admin" => Ok(ApiGroup::Admin),
+            other => Err(S5Error::Input(format!("Unknown api group: {}", other))),
+        }
+    }
+    /// Group required by the gatekeeper to call an endpoint (api.properties); admin if the route is unknown
+    pub fn required_by(endpoint: &str) -> ApiGroup {
+        match routes::route(endpoint) {
+            Some(route) => route.group,
+            None => ApiGroup::Admin,
+        }
+    }
+}
+impl Display for ApiGroup {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ApiGroup::Stats => write!(f, "stats"),
+            ApiGroup::Watcher => write!(f, "watcher"),
+            ApiGroup::Spender => write!(f, "spender"),
+            ApiGroup::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// A kid, its hex key and the groups it belongs to
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub key: String,
+    pub groups: Vec<ApiGroup>,
+}
+impl ApiKey {
+    pub fn new(id: String, key: String, groups: Vec<ApiGroup>) -> Self {
+        ApiKey { id, key, groups }
+    }
+    fn privilege(&self) -> (usize, Option<ApiGroup>) {
+        (self.groups.len(), self.groups.iter().max().copied())
+    }
+}
+
+// the key never shows up in logs or panics
+impl std::fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("id", &self.id)
+            .field("key", &"<redacted>")
+            .field("groups", &self.groups)
+            .finish()
+    }
+}
+
+/// Every key a client may sign with
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct KeyRing {
+    pub keys: Vec<ApiKey>,
+}
+impl KeyRing {
+    pub fn new(keys: Vec<ApiKey>) -> Self {
+        KeyRing { keys }
+    }
+    /// A single key whose groups are unknown; it is used for every call and the gatekeeper decides.
+    pub fn single(id: String, key: String) -> Self {
+        KeyRing::new(vec![ApiKey::new(id, key, ApiGroup::all())])
+    }
+    /// Parse the contents of a cyphernode keys.properties file
+    pub fn from_properties(contents: &str) -> Result<KeyRing, S5Error> {
+        let mut keys = vec![];
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut id = None;
+            let mut key = None;
+            let mut groups = None;
+            for statement in line.split(';') {
+                let (name, value) = match statement.split_once('=') {
+                    Some((name, value)) => (name.trim(), value.trim().trim_matches('"')),
+                    None => continue,
+                };
+                match name {
+                    "kapi_id" => id = Some(value.to_string()),
+                    "kapi_key" => key = Some(value.to_string()),
+                    // groups cngateway does not know about (eg. internal) grant nothing here
+                    "kapi_groups" => {
+                        groups = Some(
+                            value
+                                .split(',')
+                                .filter_map(|group| ApiGroup::from_str(group).ok())
+                                .collect::<Vec<ApiGroup>>(),
+                        )
+                    }
+                    _ => {}
+                }
+            }
+            match (id, key, groups) {
+                (Some(id), Some(key), Some(groups)) => keys.push(ApiKey::new(id, key, groups)),
+                (None, None, None) => continue,
+                _ => {
+                    return Err(S5Error::Input(format!(
+                        "Bad keys.properties line: {}",
+                        line.split(';').next().unwrap_or_default()
+                    )))
+                }
+            }
+        }
+        if keys.is_empty() {
+            return Err(S5Error::Input("No keys in keys.properties".to_string()));
+        }
+        Ok(KeyRing::new(keys))
+    }
+    /// Read and parse a cyphernode keys.properties file
+    pub async fn from_file(path: impl ToString) -> Result<KeyRing, S5Error> {
+        match tokio::fs::read_to_string(path.to_string()).await {
+            Ok(contents) => KeyRing::from_properties(&contents),
+            Err(e) => Err(S5Error::Input(format!("Bad Path: {}", e))),
+        }
+    }
+    /// The least privileged key that is allowed to call the endpoint
+    pub fn select(&self, endpoint: &str) -> Result<&ApiKey, S5Error> {
+        let group = ApiGroup::required_by(endpoint);
+        match self
+            .keys
+            .iter()
+            .filter(|key| key.groups.contains(&group))
+            .min_by_key(|key| key.privilege())
+        {
+            Some(key) => Ok(key),
+            None => Err(S5Error::Auth(format!(
+                "{} requires a key in the {} group; none was configured",
+                endpoint, group
+            ))),
+        }
+    }
+}
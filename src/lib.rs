@@ -1,50 +1,54 @@
-/// cyphernode gateway client
-///
-/// cngateway provides an async rust client to interact with cyphernodes gatekeeper
-/// 
-///
-/// 
-/// # Examples
-///
-/// Basic usage:
-///
-/// ```
-/// let gatekeeper_ip = "gatekeeper:2009".to_string(); // if you are connected to cyphernodeappsnet IF NOT expose gatekeeper outside network and use localhost
-/// let kid = "003".to_string();
-/// let key = "c06f9fc30c50ab7541cefaeb58708fe28babcf7d5ed1767a59685f63d0b63c54".to_string();
-/// let cert_path = "/path/to/cacert.pem";
-/// let client = CnGateway::new(
-///     gatekeeper_ip,
-///     kid,
-///     key,
-///     cert_path,
-/// )
-/// .await?;
-/// // Use bitcoin core
-/// let mempool = client.getmempoolinfo().await?;
-/// let balance = client.getbalance().await?;
-/// let address = client.getnewaddress(AddressType::Bech32,"dup".to_string()).await?; // uses the POST api format {address_type, label}
-/// // Use lightning
-/// let lninfo = client.ln_getinfo().await.unwrap();
-/// let newaddr = client.ln_newaddr().await.unwrap();
-/// let connstr = client.ln_getconnectionstring().await.unwrap();
-/// let invoice = "lnbc920u1p3khp67pp5mcqxhupukc5te86wfkryerk8f69gg9ptzcep33ry94svm4wvwzqqdqqcqzzgxqyz5vqrzjqwnvuc0u4txn35cafc7w94gxvq5p3cu9dd95f7hlrh0fvs46wpvhdjx4k0kekn630gqqqqryqqqqthqqpyrzjqw8c7yfutqqy3kz8662fxutjvef7q2ujsxtt45csu0k688lkzu3ldjx4k0kekn630gqqqqryqqqqthqqpysp58nxs2nm5wphu234ggawaeul2tnpl6jqc9a0ymfhwpr64vq0k3l4s9qypqsqlkrver3pdxm0teyye0n6y5sje8u90t4j8vpxq3qjwjh9ue46cctj2nzw8fdudfec6nd0e8gx9v485ek7p624j5leeykg70wmv59y3pqqn9ulv2".to_string();
-/// let bolt11_decoded = client.ln_decodebolt11(invoice).await.unwrap();
-/// let peer =
-///     "02eadbd9e7557375161df8b646776a547c5cbc2e95b3071ec81553f8ec2cea3b8c@18.191.253.246:9735"
-///         .to_string();
-/// let msatoshis = 3_690_000;
-/// let callback_url = "http:///yourcypherapp/callback/".to_string();
-/// let fund_stat = client
-///     .ln_connectfund(peer, msatoshis, callback_url)
-///     .await
-///     .err();
-/// let list_funds = client.ln_listfunds().await.unwrap();
-/// let list_pays = client.ln_listpays().await.unwrap();
-/// ```
-use std::time::SystemTime;
+//! cyphernode gateway client
+//!
+//! cngateway provides an async rust client to interact with cyphernodes gatekeeper
+//!
+//! # Examples
+//!
+//! Basic usage:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), cngateway::e::S5Error> {
+//! use cngateway::CnGateway;
+//! use cngateway::core::AddressType;
+//!
+//! let gatekeeper_ip = "gatekeeper:2009".to_string(); // if you are connected to cyphernodeappsnet IF NOT expose gatekeeper outside network and use localhost
+//! let kid = "003".to_string();
+//! let key = "c06f9fc30c50ab7541cefaeb58708fe28babcf7d5ed1767a59685f63d0b63c54".to_string();
+//! let cert_path = "/path/to/cacert.pem";
+//! let client = CnGateway::new(
+//!     gatekeeper_ip,
+//!     kid,
+//!     key,
+//!     cert_path,
+//! )
+//! .await?;
+//! // Use bitcoin core
+//! let mempool = client.getmempoolinfo().await?;
+//! let balance = client.getbalance().await?;
+//! let address = client.getnewaddress(AddressType::Bech32,"dup".to_string()).await?; // uses the POST api format {address_type, label}
+//! // Use lightning
+//! let lninfo = client.ln_getinfo().await.unwrap();
+//! let newaddr = client.ln_newaddr().await.unwrap();
+//! let connstr = client.ln_getconnectionstring().await.unwrap();
+//! let invoice = "lnbc920u1p3khp67pp5mcqxhupukc5te86wfkryerk8f69gg9ptzcep33ry94svm4wvwzqqdqqcqzzgxqyz5vqrzjqwnvuc0u4txn35cafc7w94gxvq5p3cu9dd95f7hlrh0fvs46wpvhdjx4k0kekn630gqqqqryqqqqthqqpyrzjqw8c7yfutqqy3kz8662fxutjvef7q2ujsxtt45csu0k688lkzu3ldjx4k0kekn630gqqqqryqqqqthqqpysp58nxs2nm5wphu234ggawaeul2tnpl6jqc9a0ymfhwpr64vq0k3l4s9qypqsqlkrver3pdxm0teyye0n6y5sje8u90t4j8vpxq3qjwjh9ue46cctj2nzw8fdudfec6nd0e8gx9v485ek7p624j5leeykg70wmv59y3pqqn9ulv2".to_string();
+//! let bolt11_decoded = client.ln_decodebolt11(invoice).await.unwrap();
+//! let peer =
+//!     "02eadbd9e7557375161df8b646776a547c5cbc2e95b3071ec81553f8ec2cea3b8c@18.191.253.246:9735"
+//!         .to_string();
+//! let msatoshis = 3_690_000;
+//! let callback_url = "http:///yourcypherapp/callback/".to_string();
+//! let fund_stat = client
+//!     .ln_connectfund(peer, msatoshis, callback_url)
+//!     .await
+//!     .err();
+//! let list_funds = client.ln_listfunds().await.unwrap();
+//! let list_pays = client.ln_listpays().await.unwrap();
+//! # Ok(())
+//! # }
+//! ```
+use std::time::{Duration, SystemTime};
 use serde::{Deserialize, Serialize};
-use reqwest::Certificate;
+use reqwest::Client;
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 
 pub mod watcher;
@@ -52,42 +56,66 @@ pub mod core;
 pub mod e;
 pub mod batcher;
 pub mod lightning;
+pub mod config;
+pub mod keys;
+pub mod callback;
+pub mod routes;
+#[cfg(any(test, feature = "test-support"))]
+pub mod mock;
+mod http;
+mod de;
 
+use crate::e::S5Error;
+use crate::config::{ClientConfig, CnGatewayBuilder};
+use crate::keys::KeyRing;
+use crate::routes::Route;
+use std::future::Future;
+use std::ops::RangeInclusive;
 use crate::core::{
-    MempoolInfo, 
+    MempoolInfo, Balances, BlockchainInfo, BlockInfo, WalletTransaction, Confirmation, Spend, SpendReq, BumpFee, BumpFeeReq, EstimateMode, EstimateSmartFeeReq, SmartFee, 
     AddressType, AddressRequest, Balance, Address
 };
 
 use crate::lightning::{
     LnBolt11, LnConnString, LnConnectFund, LnFundAddress, 
     LnInfo, LnListFunds, LnListPays,
-    LnRoutes, LnWithdraw,LnConnectFundReq, LnWithdrawReq
+    LnRoutes, LnWithdraw,LnConnectFundReq, LnWithdrawReq,
+    InvoiceAmount, LnCreateInvoiceReq, LnInvoice, LnPayReq, LnPayment, Pay, LnInvoiceStatus, LnListPeers,
 };
 use batcher::{
     AddToBatchRequest, BatchDetailResponse, BatchInfoResponse, BatchSpendRequest,
     BatchSpendResponse, CreateBatcherRequest, CreateBatcherResponse, GetBatchDetailRequest,
-    GetBatcherRequest, ListBatchersResponse, RemoveFromBatchRequest,
+    GetBatcherRequest, RemoveFromBatchRequest,
     UpdateBatcherRequest, UpdateBatcherResponse, Batchers,
 };
 use watcher::{
-    ActiveWatches, UnwatchAddress, UnwatchXpub, 
+    ActiveWatches, DerivedAddress, LabelTxns, LabelUnusedAddresses, UnwatchAddress, UnwatchXpub, UnwatchXpubLabel,
+    XpubAddressWatches, XpubWatches, WatchTxid, WatchTxidReq, UnwatchTxid, UnwatchTxidReq,
     WatchAddress, WatchXpub,WatchAddressReq, WatchXpubReq
 };
 
-const LIFETIME: u128 = 3_600_000; // 1h
+const LIFETIME: Duration = Duration::from_secs(3_600); // 1h
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     id: String,
-    exp: u128,
+    /// NumericDate, seconds since the epoch
+    exp: u64,
 }
 
 /// The gatekeeper client.
 #[derive(Clone)]
 pub struct CnGateway {
     pub host: String,
-    token: String,
-    cert: Certificate,
+    base_url: String,
+    keys: KeyRing,
+    lifetime: Duration,
+    config: ClientConfig,
+    client: Client,
+    /// client with ClientConfig::spend_timeout
+    spend_client: Client,
 }
 impl CnGateway {
     /// Initialize client with auth secrets
@@ -96,65 +124,180 @@ impl CnGateway {
         id: impl ToString,
         key: impl ToString,
         cert_path: impl ToString,
-    ) -> Result<Self, String> {
-            let cert_content = match tokio::fs::read_to_string(cert_path.to_string()).await {
-                Ok(result) => result,
-                Err(_) => return Err("Bad Path".to_string()),
-            };
-            let cert = match Certificate::from_pem(&cert_content.as_bytes()) {
-                Ok(result) => result,
-                Err(_) => return Err("Bad Path".to_string()),
-            };
-            
-            let now = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-                Ok(n) => n.as_millis(),
-                Err(_) => return Err("Clock Went Backwards!".to_string()),
-            };
-            let payload = Claims {
-                id: id.to_string(),
-                exp: now + LIFETIME,
-            };
-            let header = Header {
-                alg: Algorithm::HS256,
-                ..Default::default()
-            };
-            let token = match encode(
-                &header,
-                &payload,
-                &EncodingKey::from_secret(key.to_string().as_bytes()),
-            ) {
-                Ok(token) => token,
-                Err(_) => return Err("Error Encoding JWT!".to_string()),
-            };
-            Ok(CnGateway {
-                host: host.to_string(),
-                token: token,
-                cert: cert,
-            })
-        
+    ) -> Result<Self, S5Error> {
+        CnGateway::new_with_config(host, id, key, cert_path, ClientConfig::default()).await
+    }
+    /// Initialize client with auth secrets and custom timeouts/retries
+    pub async fn new_with_config(
+        host: impl ToString,
+        id: impl ToString,
+        key: impl ToString,
+        cert_path: impl ToString,
+        config: ClientConfig,
+    ) -> Result<Self, S5Error> {
+        CnGateway::builder()
+            .host(host)
+            .kid(id)
+            .key(key)
+            .cert_path(cert_path)
+            .config(config)
+            .build()
+            .await
+    }
+    /// Configure scheme, base path, inline certs, timeouts and user agent
+    pub fn builder() -> CnGatewayBuilder {
+        CnGatewayBuilder::new()
+    }
+    /// Initialize client with every key from a cyphernode keys.properties file.
+    /// Each call is signed with the least privileged key allowed to make it.
+    pub async fn new_with_keys(
+        host: impl ToString,
+        keys: KeyRing,
+        cert_path: impl ToString,
+    ) -> Result<Self, S5Error> {
+        CnGateway::builder()
+            .host(host)
+            .keys(keys)
+            .cert_path(cert_path)
+            .build()
+            .await
+    }
+    /// Configure from CYPHERNODE_API_KID, CYPHERNODE_API_KEY, CYPHER_GATEWAY_URL and CYPHERNODE_GATEKEEPER_CERT_CA
+    pub async fn from_env() -> Result<Self, S5Error> {
+        CnGatewayBuilder::from_env()?.build().await
+    }
+    /// Sign a fresh token for the next request to route, with the least privileged key allowed to call it
+    fn token(&self, route: &Route) -> Result<String, S5Error> {
+        let key = self.keys.select(route.path)?;
+        let now = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(n) => n.as_secs(),
+            Err(_) => return Err(S5Error::Auth("Clock Went Backwards!".to_string())),
+        };
+        let payload = Claims {
+            id: key.id.clone(),
+            exp: now + self.lifetime.as_secs(),
+        };
+        let header = Header {
+            alg: Algorithm::HS256,
+            ..Default::default()
+        };
+        match encode(
+            &header,
+            &payload,
+            &EncodingKey::from_secret(key.key.as_bytes()),
+        ) {
+            Ok(token) => Ok(token),
+            Err(e) => Err(S5Error::Auth(format!("Error Encoding JWT! {}", e))),
+        }
+    }
+    /// Run an idempotent call with a fresh token, retrying transient failures and timeouts with backoff.
+    /// Never use for calls that spend or mutate state.
+    async fn retry<T, F, Fut>(&self, route: &Route, call: F) -> Result<T, S5Error>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T, S5Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match call(self.token(route)?).await {
+                // read-only, so also resent when the first attempt may have been processed
+                Err(e) if retryable(&e) && attempt < self.config.retries => {
+                    tokio::time::sleep(self.config.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
     }
     //
     // CORE
     //
     /// Check mempool info
-    pub async fn getmempoolinfo(&self) -> Result<MempoolInfo, String> {
-        core::getmempoolinfo(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn getmempoolinfo(&self) -> Result<MempoolInfo, S5Error> {
+        self.retry(&routes::GETMEMPOOLINFO, |jwt| core::getmempoolinfo(self.base_url.clone(), jwt, &self.client)).await
     }
     /// Get balance
-    pub async fn getbalance(&self) -> Result<Balance, String> {
-        core::getbalance(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn getbalance(&self) -> Result<Balance, S5Error> {
+        self.retry(&routes::GETBALANCE, |jwt| core::getbalance(self.base_url.clone(), jwt, &self.client)).await
+    }
+    /// Wallet balance split into trusted, untrusted pending and immature
+    pub async fn getbalances(&self) -> Result<Balances, S5Error> {
+        self.retry(&routes::GETBALANCES, |jwt| core::getbalances(self.base_url.clone(), jwt, &self.client)).await
+    }
+    /// Balance of a watched xpub
+    pub async fn getbalancebyxpub(&self, xpub: impl ToString) -> Result<Balance, S5Error> {
+        let xpub = xpub.to_string();
+        self.retry(&routes::GETBALANCEBYXPUB, |jwt| core::getbalancebyxpub(self.base_url.clone(), jwt, &self.client, xpub.clone())).await
+    }
+    /// Balance of the xpub watched under label
+    pub async fn getbalancebyxpublabel(&self, label: impl ToString) -> Result<Balance, S5Error> {
+        let label = label.to_string();
+        self.retry(&routes::GETBALANCEBYXPUBLABEL, |jwt| core::getbalancebyxpublabel(self.base_url.clone(), jwt, &self.client, label.clone())).await
     }
     /// Get new address
-    pub async fn getnewaddress(&self, address_type: AddressType, label: impl ToString) -> Result<Address, String> {
+    pub async fn getnewaddress(&self, address_type: AddressType, label: impl ToString) -> Result<Address, S5Error> {
         let request = AddressRequest{
-            address_type: address_type,
+            address_type,
             label: label.to_string()
         };
-        core::getnewaddress(self.host.clone(), self.token.clone(), self.cert.clone(), request).await
+        core::getnewaddress(self.base_url.clone(), self.token(&routes::GETNEWADDRESS)?, &self.client, request).await
     }
     /// Validate onchain address
-    pub async fn validateaddress(&self, address: impl ToString) -> Result<bool, String> {
-        core::validateaddress(self.host.clone(), self.token.clone(), self.cert.clone(), address.to_string()).await
+    pub async fn validateaddress(&self, address: impl ToString) -> Result<bool, S5Error> {
+        let address = address.to_string();
+        self.retry(&routes::VALIDATEADDRESS, |jwt| core::validateaddress(self.base_url.clone(), jwt, &self.client, address.clone())).await
+    }
+    /// Chain state: tip, verification progress, softforks
+    pub async fn getblockchaininfo(&self) -> Result<BlockchainInfo, S5Error> {
+        self.retry(&routes::GETBLOCKCHAININFO, |jwt| core::getblockchaininfo(self.base_url.clone(), jwt, &self.client)).await
+    }
+    /// Hash of the block at height
+    pub async fn getblockhash(&self, height: u64) -> Result<String, S5Error> {
+        self.retry(&routes::GETBLOCKHASH, |jwt| core::getblockhash(self.base_url.clone(), jwt, &self.client, height)).await
+    }
+    /// Hash of the chain tip
+    pub async fn getbestblockhash(&self) -> Result<String, S5Error> {
+        self.retry(&routes::GETBESTBLOCKHASH, |jwt| core::getbestblockhash(self.base_url.clone(), jwt, &self.client)).await
+    }
+    /// Block header fields and txids of a block
+    pub async fn getblockinfo(&self, blockhash: impl ToString) -> Result<BlockInfo, S5Error> {
+        let blockhash = blockhash.to_string();
+        self.retry(&routes::GETBLOCKINFO, |jwt| core::getblockinfo(self.base_url.clone(), jwt, &self.client, blockhash.clone())).await
+    }
+    /// Block header fields and txids of the chain tip
+    pub async fn getbestblockinfo(&self) -> Result<BlockInfo, S5Error> {
+        self.retry(&routes::GETBESTBLOCKINFO, |jwt| core::getbestblockinfo(self.base_url.clone(), jwt, &self.client)).await
+    }
+    /// Wallet transaction by txid
+    pub async fn gettransaction(&self, txid: impl ToString) -> Result<WalletTransaction, S5Error> {
+        let txid = txid.to_string();
+        self.retry(&routes::GETTRANSACTION, |jwt| core::gettransaction(self.base_url.clone(), jwt, &self.client, txid.clone())).await
+    }
+    /// Process a wallet transaction now instead of waiting for walletnotify
+    pub async fn conf(&self, txid: impl ToString) -> Result<Confirmation, S5Error> {
+        core::conf(self.base_url.clone(), self.token(&routes::CONF)?, &self.client, txid.to_string()).await
+    }
+    /// Send amount (BTC) to address from the spending wallet; None options use its defaults
+    pub async fn spend(
+        &self,
+        address: impl ToString,
+        amount: f64,
+        conf_target: Option<u32>,
+        replaceable: Option<bool>,
+        subtractfeefromamount: Option<bool>,
+    ) -> Result<Spend, S5Error> {
+        let body = SpendReq::new(address.to_string(), amount, conf_target, replaceable, subtractfeefromamount);
+        core::spend(self.base_url.clone(), self.token(&routes::SPEND)?, &self.spend_client, body).await
+    }
+    /// Replace an unconfirmed spend with a higher fee one
+    pub async fn bumpfee(&self, txid: impl ToString, conf_target: Option<u32>) -> Result<BumpFee, S5Error> {
+        let body = BumpFeeReq::new(txid.to_string(), conf_target);
+        core::bumpfee(self.base_url.clone(), self.token(&routes::BUMPFEE)?, &self.spend_client, body).await
+    }
+    /// Feerate for a transaction to confirm within conf_target blocks
+    pub async fn estimatesmartfee(&self, conf_target: u32, estimate_mode: Option<EstimateMode>) -> Result<SmartFee, S5Error> {
+        let body = EstimateSmartFeeReq::new(conf_target, estimate_mode);
+        core::estimatesmartfee(self.base_url.clone(), self.token(&routes::BITCOIN_ESTIMATESMARTFEE)?, &self.client, body).await
     }
     //
     // BATCHER
@@ -163,18 +306,18 @@ impl CnGateway {
         &self,
         batcher_label: impl ToString,
         conf_target: u64,
-    ) -> Result<CreateBatcherResponse, String> {
+    ) -> Result<CreateBatcherResponse, S5Error> {
         let request = CreateBatcherRequest::new(batcher_label.to_string(), conf_target);
-        batcher::createbatcher(self.host.clone(), self.token.clone(), self.cert.clone(), request).await
+        batcher::createbatcher(self.base_url.clone(), self.token(&routes::CREATEBATCHER)?, &self.client, request).await
     }
     pub async fn updatebatcher(
         &self,
         batcher_label: Option<String>,
         batcher_id: Option<String>,
         conf_target: u64,
-    ) -> Result<UpdateBatcherResponse, String> {
+    ) -> Result<UpdateBatcherResponse, S5Error> {
         let request = UpdateBatcherRequest::new(batcher_label, batcher_id, conf_target);
-        batcher::updatebatcher(self.host.clone(), self.token.clone(), self.cert.clone(), request).await
+        batcher::updatebatcher(self.base_url.clone(), self.token(&routes::UPDATEBATCHER)?, &self.client, request).await
     }
     pub async fn addtobatch(
         &self,
@@ -182,42 +325,42 @@ impl CnGateway {
         amount: f64,
         batcher_label: impl ToString,
         webhook_url: Option<String>,
-    ) -> Result<BatchInfoResponse, String> {
+    ) -> Result<BatchInfoResponse, S5Error> {
         let request = AddToBatchRequest::new(address.to_string(), amount, batcher_label.to_string(), webhook_url);
-        batcher::addtobatch(self.host.clone(), self.token.clone(), self.cert.clone(), request).await
+        batcher::addtobatch(self.base_url.clone(), self.token(&routes::ADDTOBATCH)?, &self.client, request).await
     }
-    pub async fn removefrombatch(&self, output_id: u64) -> Result<BatchInfoResponse, String> {
+    pub async fn removefrombatch(&self, output_id: u64) -> Result<BatchInfoResponse, S5Error> {
         let request = RemoveFromBatchRequest::new(output_id);
-        batcher::removefrombatch(self.host.clone(), self.token.clone(), self.cert.clone(), request).await
+        batcher::removefrombatch(self.base_url.clone(), self.token(&routes::REMOVEFROMBATCH)?, &self.client, request).await
     }
     pub async fn getbatcher(
         &self,
         batcher_label: Option<String>,
         batcher_id: Option<String>,
-    ) -> Result<BatchInfoResponse, String> {
+    ) -> Result<BatchInfoResponse, S5Error> {
         let request = GetBatcherRequest::new(batcher_label, batcher_id);
-        batcher::getbatcher(self.host.clone(), self.token.clone(), self.cert.clone(), request).await
+        batcher::getbatcher(self.base_url.clone(), self.token(&routes::GETBATCHER)?, &self.client, request).await
     }
     pub async fn getbatchdetails(
         &self,
         batcher_id: u64,
         batcher_label: Option<String>,
         txid: Option<String>,
-    ) -> Result<BatchDetailResponse, String> {
+    ) -> Result<BatchDetailResponse, S5Error> {
         let request = GetBatchDetailRequest::new(batcher_id, batcher_label, txid);
-        batcher::getbatchdetails(self.host.clone(), self.token.clone(), self.cert.clone(), request).await
+        batcher::getbatchdetails(self.base_url.clone(), self.token(&routes::GETBATCHDETAILS)?, &self.client, request).await
     }
-    pub async fn listbatchers(&self) -> Result<Batchers, String> {
-        batcher::listbatchers(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn listbatchers(&self) -> Result<Batchers, S5Error> {
+        self.retry(&routes::LISTBATCHERS, |jwt| batcher::listbatchers(self.base_url.clone(), jwt, &self.client)).await
     }
     pub async fn batchspend(
         &self,
         batcher_label: Option<String>,
         batcher_id: Option<String>,
         conf_target: Option<u64>,
-    ) -> Result<BatchSpendResponse, String> {
+    ) -> Result<BatchSpendResponse, S5Error> {
         let request = BatchSpendRequest::new(batcher_label, batcher_id, conf_target);
-        batcher::batchspend(self.host.clone(), self.token.clone(), self.cert.clone(), request).await
+        batcher::batchspend(self.base_url.clone(), self.token(&routes::BATCHSPEND)?, &self.spend_client, request).await
     }
     //
     // WATCHER
@@ -229,7 +372,7 @@ impl CnGateway {
         confirmed_callback_url: impl ToString,
         label: impl ToString,
         event_message: Option<String>,
-    ) -> Result<WatchAddress, String> {
+    ) -> Result<WatchAddress, S5Error> {
         let body = WatchAddressReq::new(
             address.to_string(),
             unconfirmed_callback_url.to_string(),
@@ -237,12 +380,38 @@ impl CnGateway {
             event_message,
             label.to_string(),
         );
-        watcher::watch(self.host.clone(), self.token.clone(), self.cert.clone(), body).await
+        watcher::watch(self.base_url.clone(), self.token(&routes::WATCH)?, &self.client, body).await
+    }
+    /// Watch a txid: confirmed_callback_url is called at 1 confirmation, xconf_callback_url at nbxconf confirmations
+    pub async fn watchtxid(
+        &self,
+        txid: impl ToString,
+        confirmed_callback_url: impl ToString,
+        xconf_callback_url: impl ToString,
+        nbxconf: u32,
+    ) -> Result<WatchTxid, S5Error> {
+        let body = WatchTxidReq::new(
+            txid.to_string(),
+            confirmed_callback_url.to_string(),
+            xconf_callback_url.to_string(),
+            nbxconf,
+        );
+        watcher::watchtxid(self.base_url.clone(), self.token(&routes::WATCHTXID)?, &self.client, body).await
+    }
+    /// Unwatch a txid; without callback urls every watch on it is removed
+    pub async fn unwatchtxid(
+        &self,
+        txid: impl ToString,
+        confirmed_callback_url: Option<String>,
+        xconf_callback_url: Option<String>,
+    ) -> Result<UnwatchTxid, S5Error> {
+        let body = UnwatchTxidReq::new(txid.to_string(), confirmed_callback_url, xconf_callback_url);
+        watcher::unwatchtxid(self.base_url.clone(), self.token(&routes::UNWATCHTXID)?, &self.client, body).await
     }
     /// Unwatch a bitcoin address
-    pub async fn unwatch(&self, address: String) -> Result<UnwatchAddress, String> {
+    pub async fn unwatch(&self, address: String) -> Result<UnwatchAddress, S5Error> {
 
-        watcher::unwatch(self.host.clone(), self.token.clone(), self.cert.clone(), address).await
+        watcher::unwatch(self.base_url.clone(), self.token(&routes::UNWATCH)?, &self.client, address).await
     }
     /// Get addresses currently being watched
     pub async fn watchxpub(
@@ -253,7 +422,7 @@ impl CnGateway {
         nstart: i64,
         unconfirmed_callback_url: impl ToString,
         confirmed_callback_url: impl ToString,
-    ) -> Result<WatchXpub, String> {
+    ) -> Result<WatchXpub, S5Error> {
         let body = WatchXpubReq::new(
             label.to_string(),
             pub32.to_string(),
@@ -262,34 +431,89 @@ impl CnGateway {
             unconfirmed_callback_url.to_string(),
             confirmed_callback_url.to_string(),
         );
-        watcher::watchxpub(self.host.clone(), self.token.clone(), self.cert.clone(), body).await
+        watcher::watchxpub(self.base_url.clone(), self.token(&routes::WATCHXPUB)?, &self.client, body).await
     }
     /// Unwatch a bitcoin xpub
-    pub async fn unwatchxpubbyxpub(&self, xpub: impl ToString) -> Result<UnwatchXpub, String> {
-        watcher::unwatchxpubbyxpub(self.host.clone(), self.token.clone(), self.cert.clone(), xpub.to_string()).await
+    pub async fn unwatchxpubbyxpub(&self, xpub: impl ToString) -> Result<UnwatchXpub, S5Error> {
+        watcher::unwatchxpubbyxpub(self.base_url.clone(), self.token(&routes::UNWATCHXPUBBYXPUB)?, &self.client, xpub.to_string()).await
+    }
+    /// Unwatch the xpub watched with label
+    pub async fn unwatchxpubbylabel(&self, label: impl ToString) -> Result<UnwatchXpubLabel, S5Error> {
+        watcher::unwatchxpubbylabel(self.base_url.clone(), self.token(&routes::UNWATCHXPUBBYLABEL)?, &self.client, label.to_string()).await
+    }
+    /// Get xpubs currently being watched
+    pub async fn getactivexpubwatches(&self) -> Result<XpubWatches, S5Error> {
+        self.retry(&routes::GETACTIVEXPUBWATCHES, |jwt| watcher::getactivexpubwatches(self.base_url.clone(), jwt, &self.client)).await
+    }
+    /// Addresses at range of the xpub and path configured in cyphernode
+    pub async fn deriveindex(&self, range: RangeInclusive<u32>) -> Result<Vec<DerivedAddress>, S5Error> {
+        self.retry(&routes::DERIVEINDEX, |jwt| watcher::deriveindex(self.base_url.clone(), jwt, &self.client, range.clone())).await
+    }
+    /// Addresses at path/range of pub32, eg. ("0", 25..=30) for 0/25-30
+    pub async fn derivepubpath(
+        &self,
+        pub32: impl ToString,
+        path: impl ToString,
+        range: RangeInclusive<u32>,
+    ) -> Result<Vec<DerivedAddress>, S5Error> {
+        watcher::derivepubpath(self.base_url.clone(), self.token(&routes::DERIVEPUBPATH)?, &self.client, pub32.to_string(), path.to_string(), range).await
+    }
+    /// deriveindex using bitcoind
+    pub async fn deriveindex_bitcoind(&self, range: RangeInclusive<u32>) -> Result<Vec<DerivedAddress>, S5Error> {
+        self.retry(&routes::DERIVEINDEX_BITCOIND, |jwt| watcher::deriveindex_bitcoind(self.base_url.clone(), jwt, &self.client, range.clone())).await
+    }
+    /// derivepubpath using bitcoind
+    pub async fn derivepubpath_bitcoind(
+        &self,
+        pub32: impl ToString,
+        path: impl ToString,
+        range: RangeInclusive<u32>,
+    ) -> Result<Vec<DerivedAddress>, S5Error> {
+        watcher::derivepubpath_bitcoind(self.base_url.clone(), self.token(&routes::DERIVEPUBPATH_BITCOIND)?, &self.client, pub32.to_string(), path.to_string(), range).await
+    }
+    /// Get derived addresses currently being watched for an xpub
+    pub async fn getactivewatchesbyxpub(&self, xpub: impl ToString) -> Result<XpubAddressWatches, S5Error> {
+        let xpub = xpub.to_string();
+        self.retry(&routes::GETACTIVEWATCHESBYXPUB, |jwt| watcher::getactivewatchesbyxpub(self.base_url.clone(), jwt, &self.client, xpub.clone())).await
+    }
+    /// Get derived addresses currently being watched for the xpub watched with label
+    pub async fn getactivewatchesbylabel(&self, label: impl ToString) -> Result<XpubAddressWatches, S5Error> {
+        let label = label.to_string();
+        self.retry(&routes::GETACTIVEWATCHESBYLABEL, |jwt| watcher::getactivewatchesbylabel(self.base_url.clone(), jwt, &self.client, label.clone())).await
     }
     /// Get addresses currently being watched
-    pub async fn getactivewatches(&self) -> Result<ActiveWatches, String> {
-        watcher::getactivewatches(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn getactivewatches(&self) -> Result<ActiveWatches, S5Error> {
+        self.retry(&routes::GETACTIVEWATCHES, |jwt| watcher::getactivewatches(self.base_url.clone(), jwt, &self.client)).await
+    }
+    /// Get transactions received by addresses watched with label; count defaults to 10 on cyphernode
+    pub async fn get_txns_by_watchlabel(&self, label: impl ToString, count: Option<u32>) -> Result<LabelTxns, S5Error> {
+        let label = label.to_string();
+        self.retry(&routes::GET_TXNS_BY_WATCHLABEL, |jwt| watcher::get_txns_by_watchlabel(self.base_url.clone(), jwt, &self.client, label.clone(), count)).await
+    }
+    /// Get addresses of the xpub watched with label that never received a transaction; count defaults to 10 on cyphernode
+    pub async fn get_unused_addresses_by_watchlabel(&self, label: impl ToString, count: Option<u32>) -> Result<LabelUnusedAddresses, S5Error> {
+        let label = label.to_string();
+        self.retry(&routes::GET_UNUSED_ADDRESSES_BY_WATCHLABEL, |jwt| watcher::get_unused_addresses_by_watchlabel(self.base_url.clone(), jwt, &self.client, label.clone(), count)).await
     }
     //
     // LIGHTNING
     //
     /// Ln node info
-    pub async fn ln_getinfo(&self) -> Result<LnInfo, String> {
-        lightning::ln_getinfo(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn ln_getinfo(&self) -> Result<LnInfo, S5Error> {
+        self.retry(&routes::LN_GETINFO, |jwt| lightning::ln_getinfo(self.base_url.clone(), jwt, &self.client)).await
     }
     /// Get new address to deposit funds to open channels with
-    pub async fn ln_newaddr(&self) -> Result<LnFundAddress, String> {
-        lightning::ln_newaddr(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn ln_newaddr(&self) -> Result<LnFundAddress, S5Error> {
+        lightning::ln_newaddr(self.base_url.clone(), self.token(&routes::LN_NEWADDR)?, &self.client).await
     }
     /// Get your nodes connection string to share with peers
-    pub async fn ln_getconnectionstring(&self) -> Result<LnConnString, String> {
-        lightning::ln_getconnectionstring(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn ln_getconnectionstring(&self) -> Result<LnConnString, S5Error> {
+        self.retry(&routes::LN_GETCONNECTIONSTRING, |jwt| lightning::ln_getconnectionstring(self.base_url.clone(), jwt, &self.client)).await
     }
     /// Decode an invoice
-    pub async fn ln_decodebolt11(&self, invoice: impl ToString) -> Result<LnBolt11, String> {
-        lightning::ln_decodebolt11(self.host.clone(), self.token.clone(), self.cert.clone(), invoice.to_string()).await
+    pub async fn ln_decodebolt11(&self, invoice: impl ToString) -> Result<LnBolt11, S5Error> {
+        let invoice = invoice.to_string();
+        self.retry(&routes::LN_DECODEBOLT11, |jwt| lightning::ln_decodebolt11(self.base_url.clone(), jwt, &self.client, invoice.clone())).await
     }
     /// Connect to a given peer and attempt opening a channel and fund it with msatoshis. Get notified at callback_url.
     pub async fn ln_connectfund(
@@ -297,17 +521,57 @@ impl CnGateway {
         peer: impl ToString,
         msatoshis: u128,
         callback_url: impl ToString,
-    ) -> Result<LnConnectFund, String> {
+    ) -> Result<LnConnectFund, S5Error> {
         let body = LnConnectFundReq::new(peer.to_string(), msatoshis, callback_url.to_string());
-        lightning::ln_connectfund(self.host.clone(), self.token.clone(), self.cert.clone(), body).await
+        lightning::ln_connectfund(self.base_url.clone(), self.token(&routes::LN_CONNECTFUND)?, &self.spend_client, body).await
+    }
+    /// Create a bolt11 invoice; callback_url is called once it is paid
+    pub async fn ln_create_invoice(
+        &self,
+        msatoshi: InvoiceAmount,
+        label: impl ToString,
+        description: impl ToString,
+        expiry: Option<u64>,
+        callback_url: Option<String>,
+    ) -> Result<LnInvoice, S5Error> {
+        let body = LnCreateInvoiceReq::new(msatoshi, label.to_string(), description.to_string(), expiry, callback_url);
+        lightning::ln_create_invoice(self.base_url.clone(), self.token(&routes::LN_CREATE_INVOICE)?, &self.client, body).await
+    }
+    /// Pay a bolt11 invoice; refused if it does not match expected_msatoshi or expected_description
+    pub async fn ln_pay(
+        &self,
+        bolt11: impl ToString,
+        expected_msatoshi: Option<u64>,
+        expected_description: Option<String>,
+    ) -> Result<LnPayment, S5Error> {
+        let body = LnPayReq::new(bolt11.to_string(), expected_msatoshi, expected_description);
+        lightning::ln_pay(self.base_url.clone(), self.token(&routes::LN_PAY)?, &self.spend_client, body).await
+    }
+    /// Status of the payment of an invoice by bolt11 or payment hash, from ln_listpays; None if it was never paid
+    pub async fn ln_findpay(&self, bolt11_or_payment_hash: impl ToString) -> Result<Option<Pay>, S5Error> {
+        let pays = self.ln_listpays().await?;
+        Ok(pays.find(&bolt11_or_payment_hash.to_string()).cloned())
+    }
+    /// Status of the invoice created with label; None if there is none
+    pub async fn ln_getinvoice(&self, label: impl ToString) -> Result<Option<LnInvoiceStatus>, S5Error> {
+        let label = label.to_string();
+        self.retry(&routes::LN_GETINVOICE, |jwt| lightning::ln_getinvoice(self.base_url.clone(), jwt, &self.client, label.clone())).await
+    }
+    /// Delete the invoice created with label, eg. once expired
+    pub async fn ln_delinvoice(&self, label: impl ToString) -> Result<LnInvoiceStatus, S5Error> {
+        lightning::ln_delinvoice(self.base_url.clone(), self.token(&routes::LN_DELINVOICE)?, &self.client, label.to_string()).await
+    }
+    /// Returns the peers and their channels, including offline peers
+    pub async fn ln_listpeers(&self) -> Result<LnListPeers, S5Error> {
+        self.retry(&routes::LN_LISTPEERS, |jwt| lightning::ln_listpeers(self.base_url.clone(), jwt, &self.client)).await
     }
     /// Returns the list of unused outputs and funds in open channels
-    pub async fn ln_listfunds(&self) -> Result<LnListFunds, String> {
-        lightning::ln_listfunds(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn ln_listfunds(&self) -> Result<LnListFunds, S5Error> {
+        self.retry(&routes::LN_LISTFUNDS, |jwt| lightning::ln_listfunds(self.base_url.clone(), jwt, &self.client)).await
     }
     /// Returns history of paid invoices
-    pub async fn ln_listpays(&self) -> Result<LnListPays, String> {
-        lightning::ln_listpays(self.host.clone(), self.token.clone(), self.cert.clone()).await
+    pub async fn ln_listpays(&self) -> Result<LnListPays, S5Error> {
+        self.retry(&routes::LN_LISTPAYS, |jwt| lightning::ln_listpays(self.base_url.clone(), jwt, &self.client)).await
     }
     /// Returns an array representing hops of nodes to get to the destination node from our node
     pub async fn ln_getroute(
@@ -315,15 +579,17 @@ impl CnGateway {
         node_id: String,
         msatoshis: u128,
         risk_factor: f32,
-    ) -> Result<LnRoutes, String> {
-        lightning::ln_getroute(
-            self.host.clone(),
-            self.token.clone(),
-            self.cert.clone(),
-            node_id,
-            msatoshis,
-            risk_factor,
-        )
+    ) -> Result<LnRoutes, S5Error> {
+        self.retry(&routes::LN_GETROUTE, |jwt| {
+            lightning::ln_getroute(
+                self.base_url.clone(),
+                jwt,
+                &self.client,
+                node_id.clone(),
+                msatoshis,
+                risk_factor,
+            )
+        })
         .await
     }
     /// Withdraw funds from channel back on main chain
@@ -332,70 +598,332 @@ impl CnGateway {
         address: impl ToString,
         satoshis: u128,
         feerate: impl ToString,
-    ) -> Result<LnWithdraw, String> {
+    ) -> Result<LnWithdraw, S5Error> {
         let body = LnWithdrawReq::new(address.to_string(), satoshis, feerate.to_string());
-        lightning::ln_withdraw(self.host.clone(), self.token.clone(), self.cert.clone(), body).await
+        lightning::ln_withdraw(self.base_url.clone(), self.token(&routes::LN_WITHDRAW)?, &self.spend_client, body).await
     }
 }
+
+/// Failures a read-only call is resent after: transient ones, plus timeouts and 504s since reading twice is harmless
+fn retryable(e: &S5Error) -> bool {
+    e.is_transient() || matches!(e, S5Error::Interrupted(_) | S5Error::Unavailable { .. })
+}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock::MockGatekeeper;
 
     #[tokio::test]
-    async fn local_bitcoin_testnet() {
-        let client = new_client_localhost().await;
+    async fn mock_core_addresses() {
+        let (gatekeeper, client) = new_client_mock().await;
 
-        let mempool = client.getmempoolinfo().await.unwrap();
         let address = client.getnewaddress(AddressType::Bech32,"dup".to_string()).await.unwrap();
         let validate_ok = client.validateaddress(address.clone().address).await.unwrap();
         let validate_bad = client.validateaddress("bc1hahahaha".to_string()).await.unwrap();
         assert!(validate_ok);
         assert!(!validate_bad);
+
+        let requests = gatekeeper.requests_to(&routes::GETNEWADDRESS);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].body.as_ref().unwrap()["label"], "dup");
+        let requests = gatekeeper.requests_to(&routes::VALIDATEADDRESS);
+        assert_eq!(requests[0].path, format!("/v0/validateaddress/{}", address.address));
+    }
+
+    #[tokio::test]
+    async fn mock_core_balances() {
+        let (gatekeeper, client) = new_client_mock().await;
+
         let balance = client.getbalance().await.unwrap();
-        println!("mempool: {:#?}", mempool);
-        println!("address: {:#?}", address);
-        println!("balance: {:#?}", balance);
+        assert_eq!(balance.balance, 1.51911837);
+        let balances = client.getbalances().await.unwrap().balances.mine;
+        assert_eq!(balances.trusted, 1.29979716);
+        assert_eq!(balances.untrusted_pending, 0.22);
+        let xpub = "upub5GtUcgGed1aGH4HKQ3vMYrsmLXwmHhS1AeX33ZvDgZiyvkGhNTvGd2TA5Lr4v239Fzjj4ZY48t6wTtXUy2yRgapf37QHgt6KWEZ6bgsCLpb";
+        assert_eq!(client.getbalancebyxpub(xpub).await.unwrap().balance, 0.00316);
+        assert_eq!(client.getbalancebyxpublabel("2219").await.unwrap().balance, 0.00316);
+
+        let requests = gatekeeper.requests_to(&routes::GETBALANCEBYXPUB);
+        assert_eq!(requests[0].path, format!("/v0/getbalancebyxpub/{}", xpub));
+        let requests = gatekeeper.requests_to(&routes::GETBALANCEBYXPUBLABEL);
+        assert_eq!(requests[0].path, "/v0/getbalancebyxpublabel/2219");
+    }
+
+    #[tokio::test]
+    async fn mock_core_chain() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let mempool = client.getmempoolinfo().await.unwrap();
+        assert_eq!(mempool.size, 25);
+        let chain = client.getblockchaininfo().await.unwrap();
+        assert!(chain.verificationprogress > 0.99);
+        match chain.softforks {
+            core::Softforks::ByName(forks) => {
+                let taproot = forks["taproot"].bip9.clone().unwrap();
+                assert_eq!(taproot.status, core::Bip9Status::Active);
+            }
+            core::Softforks::Legacy(_) => panic!("expected softforks by name"),
+        }
+        let tip = client.getbestblockhash().await.unwrap();
+        assert_eq!(client.getblockhash(2403010).await.unwrap(), tip);
+        let block = client.getblockinfo(&tip).await.unwrap();
+        assert_eq!(block.height, 2403010);
+        assert_eq!(client.getbestblockinfo().await.unwrap(), block);
 
+        let requests = gatekeeper.requests_to(&routes::GETBLOCKHASH);
+        assert_eq!(requests[0].path, "/v0/getblockhash/2403010");
+        let requests = gatekeeper.requests_to(&routes::GETBLOCKINFO);
+        assert_eq!(requests[0].path, format!("/v0/getblockinfo/{}", tip));
     }
 
     #[tokio::test]
-    async fn local_batcher_testnet() {
-        let client = new_client_localhost().await;
+    async fn mock_core_transactions() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let txid = "af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648";
+        let tx = client.gettransaction(txid).await.unwrap();
+        assert_eq!(tx.txid, txid);
+        assert_eq!(tx.fee, Some(-0.00000141));
+        assert_eq!(tx.blockhash.as_deref(), Some("000000006f82a384c208ecfa04d05beea02d420f3f398ddda5c7f900de5718ea"));
+        assert_eq!(tx.details[0].category, core::TransactionCategory::Send);
+        assert_eq!(client.conf(txid).await.unwrap().result, "confirmed");
+
+        let requests = gatekeeper.requests_to(&routes::CONF);
+        assert_eq!(requests[0].path, format!("/v0/conf/{}", txid));
+    }
+
+    #[tokio::test]
+    async fn mock_core_spend() {
+        let (gatekeeper, client) = new_client_mock().await;
 
-        // default slow and fast batchers already exist
-        // let batcher = client.createbatcher("sm11p".to_string(), 3).await.unwrap();
-        // println!("batcher: {:#?}", batcher);
         let address = "tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va";
-        let amount = 0.00003000;
+        let txid = "af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648";
+        let spend = client.spend(address, 0.00233, Some(6), None, Some(true)).await.unwrap();
+        assert_eq!(spend.txid, txid);
+        let details = spend.details.unwrap();
+        assert_eq!(details.vsize, 141);
+        assert!(details.subtractfeefromamount);
+        match client.spend(address, 0.0, None, None, None).await {
+            Err(S5Error::Gatekeeper(message)) => assert_eq!(message, "Invalid amount"),
+            other => panic!("expected a rejected spend, got {:?}", other),
+        }
+        match client.spend(address, 42.0, None, None, None).await {
+            Err(S5Error::Gatekeeper(message)) => assert_eq!(message, "spend rejected"),
+            other => panic!("expected a rejected spend, got {:?}", other),
+        }
+        let bumped = client.bumpfee(txid, None).await.unwrap();
+        assert!(bumped.fee > bumped.origfee);
+
+        let requests = gatekeeper.requests_to(&routes::SPEND);
+        assert_eq!(requests.len(), 3);
+        let spend_body = requests[0].body.as_ref().unwrap();
+        assert_eq!(spend_body["confTarget"], 6);
+        assert!(spend_body.get("replaceable").is_none());
+        let requests = gatekeeper.requests_to(&routes::BUMPFEE);
+        assert_eq!(requests[0].body.as_ref().unwrap(), &serde_json::json!({ "txid": txid }));
+    }
+
+    #[tokio::test]
+    async fn mock_core_estimatesmartfee() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let fee = client.estimatesmartfee(2, Some(EstimateMode::Economical)).await.unwrap();
+        assert_eq!(fee.blocks, 2);
+        assert_eq!(fee.sat_per_vb(), Some(1.0));
+        match client.estimatesmartfee(0, None).await {
+            Err(S5Error::Gatekeeper(message)) => assert!(message.starts_with("Invalid conf_target")),
+            other => panic!("expected an rpc error, got {:?}", other),
+        }
+
+        let requests = gatekeeper.requests_to(&routes::BITCOIN_ESTIMATESMARTFEE);
+        assert_eq!(
+            requests[0].body.as_ref().unwrap(),
+            &serde_json::json!({ "confTarget": 2, "estimateMode": "ECONOMICAL" })
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_batcher_configure() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let batcher = client.createbatcher("sm11p".to_string(), 3).await.unwrap();
+        assert_eq!(batcher.batcher_id, 5);
+        let updated = client.updatebatcher(Some("fast".to_string()), None, 2).await.unwrap();
+        assert_eq!(updated.conf_target, 2);
+        let batchers = client.listbatchers().await.unwrap();
+        assert_eq!(batchers.len(), 1);
+
+        assert!(gatekeeper.requests().iter().all(|request| request.kid.as_deref() == Some("002")));
+    }
+
+    #[tokio::test]
+    async fn mock_batcher_outputs() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let address = "tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va";
+        let add_status = client.addtobatch(address, 0.00003000, "default", None).await.unwrap();
+        assert_eq!(add_status.output_id, 34);
+        let remove_status = client.removefrombatch(add_status.output_id).await.unwrap();
+        assert_eq!(remove_status.batcher_id, 1);
+
+        let requests = gatekeeper.requests_to(&routes::ADDTOBATCH);
+        assert_eq!(requests[0].body.as_ref().unwrap()["address"], address);
+        assert!(gatekeeper.requests().iter().all(|request| request.kid.as_deref() == Some("002")));
+    }
+
+    #[tokio::test]
+    async fn mock_batcher_spend() {
+        let (gatekeeper, client) = new_client_mock().await;
+
         let batcher_label = "default";
-        let batcher_id = 1;
-        let webhook_url: Option<String> = None;
+        let info = client.getbatcher(Some(batcher_label.to_string()), None).await.unwrap();
+        assert_eq!(info.nb_outputs, 7);
+        let batch_details = client.getbatchdetails(1, None, None).await.unwrap();
+        assert_eq!(batch_details.outputs.unwrap()[0].address, "tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va");
+        let spend_status = client.batchspend(Some(batcher_label.to_string()), None, None).await.unwrap();
+        assert_eq!(spend_status.status, "accepted");
+
+        assert_eq!(gatekeeper.requests_to(&routes::BATCHSPEND).len(), 1);
+        assert!(gatekeeper.requests().iter().all(|request| request.kid.as_deref() == Some("002")));
+    }
 
-        let batch_details = client.getbatchdetails(batcher_id, None, None).await.unwrap();
-        println!("batch_details: {:#?}", batch_details);
+    #[tokio::test]
+    async fn mock_watch_address() {
+        let (gatekeeper, client) = new_client_mock().await;
 
-        // let add_status = client.addtobatch(address, amount, batcher_label, webhook_url.clone()).await.unwrap();
-        // println!("add_status: {:#?}", add_status);
+        let address = "tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va";
+        let watch = client
+            .watch(address, "http://app/callback0conf", "http://app/callback1conf", "hot", None)
+            .await
+            .unwrap();
+        assert_eq!(watch.address, address);
+        assert_eq!(watch.label, "hot");
+        let watches = client.getactivewatches().await.unwrap();
+        assert_eq!(watches.watches[0].address, address);
+        let unwatch = client.unwatch(address.to_string()).await.unwrap();
+        assert_eq!(unwatch.address, address);
 
-        let batchers =client.listbatchers().await.unwrap();
-        println!("batchers: {:#?}", batchers);
+        assert_eq!(gatekeeper.requests_to(&routes::WATCH)[0].method, "POST");
+        let requests = gatekeeper.requests_to(&routes::UNWATCH);
+        assert_eq!(requests[0].path, format!("/v0/unwatch/{}", address));
+        assert!(gatekeeper.requests().iter().all(|request| request.kid.as_deref() == Some("001")));
+    }
 
-        let batcher1 =client.getbatchdetails(batcher_id, None, None).await.unwrap();
-        println!("batcher1: {:#?}", batcher1);
+    #[tokio::test]
+    async fn mock_watch_label() {
+        let (gatekeeper, client) = new_client_mock().await;
 
-        // let spend_status = client.batchspend(Some(batcher_label.to_string()), None, None).await.unwrap();
-        // println!("spend_status: {:#?}", spend_status);
+        let txns = client.get_txns_by_watchlabel("hot", Some(2)).await.unwrap();
+        assert_eq!(txns.label_txns[0].confirmations, 1);
+        assert_eq!(txns.label_txns[0].blockheight, Some(1817509));
+        assert_eq!(txns.label_txns[0].amount, 0.00002545);
+        assert_eq!(txns.label_txns[1].v_out, 1);
+        assert_eq!(txns.label_txns[1].blockheight, None);
+        let unused = client.get_unused_addresses_by_watchlabel("hot", Some(3)).await.unwrap();
+        assert_eq!(unused.label_unused_addresses.len(), 3);
+        assert_eq!(unused.label_unused_addresses[0].address_pub32_index, 56);
+        assert_eq!(unused.label_unused_addresses[0].pub32_label, "hot");
 
+        let requests = gatekeeper.requests_to(&routes::GET_TXNS_BY_WATCHLABEL);
+        assert_eq!(requests[0].path, "/v0/get_txns_by_watchlabel/hot/2");
+        let requests = gatekeeper.requests_to(&routes::GET_UNUSED_ADDRESSES_BY_WATCHLABEL);
+        assert_eq!(requests[0].path, "/v0/get_unused_addresses_by_watchlabel/hot/3");
     }
+
     #[tokio::test]
-    async fn local_ln_testnet() {
-        let client = new_client_localhost().await;
+    async fn mock_watch_xpub() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let xpub = "tpubD6NzVbkrYhZ4YR3QK2tyfMMvBghAvqtNaNK1LTyDWcRHLcMUm3ZN2cGm5BS3MhCRCeCkXQkTXXjiJgqxpqXK7PeUSp86DTTgkLpcjMtpKWk";
+        let xpub_watches = client.getactivexpubwatches().await.unwrap();
+        assert_eq!(xpub_watches.watches[0].last_imported_n, 121);
+        let by_xpub = client.getactivewatchesbyxpub(xpub).await.unwrap();
+        assert!(by_xpub.watches[0].imported);
+        let by_label = client.getactivewatchesbylabel("hot").await.unwrap();
+        assert_eq!(by_label.watches[0].pub32_index, 0);
+        let unwatchxpub = client.unwatchxpubbyxpub(xpub).await.unwrap();
+        assert_eq!(unwatchxpub.pub32, xpub);
+        let unwatch_label = client.unwatchxpubbylabel("hot").await.unwrap();
+        assert_eq!(unwatch_label.label, "hot");
+
+        let requests = gatekeeper.requests_to(&routes::GETACTIVEWATCHESBYXPUB);
+        assert_eq!(requests[0].path, format!("/v0/getactivewatchesbyxpub/{}", xpub));
+        let requests = gatekeeper.requests_to(&routes::UNWATCHXPUBBYLABEL);
+        assert_eq!(requests[0].path, "/v0/unwatchxpubbylabel/hot");
+        assert!(gatekeeper.requests().iter().all(|request| request.kid.as_deref() == Some("001")));
+    }
+
+    #[tokio::test]
+    async fn mock_watch_txid() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let txid = "b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387";
+        let watch_txid = client
+            .watchtxid(txid, "http://app/callback1conf", "http://app/callback6conf", 6)
+            .await
+            .unwrap();
+        assert_eq!(watch_txid.nbxconf, 6);
+        assert!(watch_txid.inserted);
+        let unwatch_txid = client.unwatchtxid(txid, None, None).await.unwrap();
+        assert_eq!(unwatch_txid.txid, txid);
+
+        let requests = gatekeeper.requests_to(&routes::WATCHTXID);
+        assert_eq!(requests[0].body.as_ref().unwrap()["xconfCallbackURL"], "http://app/callback6conf");
+        let requests = gatekeeper.requests_to(&routes::UNWATCHTXID);
+        assert_eq!(requests[0].body, Some(serde_json::json!({ "txid": txid })));
+    }
+
+    #[tokio::test]
+    async fn mock_derive() {
+        let (gatekeeper, client) = new_client_mock().await;
+        let pub32 = "upub5GtUcgGed1aGH4HKQ3vMYrsmLXwmHhS1AeX33ZvDgZiyvkGhNTvGd2TA5Lr4v239Fzjj4ZY48t6wTtXUy2yRgapf37QHgt6KWEZ6bgsCLpb";
+
+        let configured = client.deriveindex(25..=30).await.unwrap();
+        assert_eq!(configured.len(), 6);
+        assert_eq!(configured[0].index, 25);
+        assert_eq!(configured[0].path, None);
+        assert_eq!(client.deriveindex_bitcoind(25..=30).await.unwrap(), configured);
+
+        let receive = client.derivepubpath(pub32, "0", 25..=30).await.unwrap();
+        assert_eq!(receive[5].index, 30);
+        assert_eq!(receive[5].path.as_deref(), Some("0/30"));
+        assert_eq!(receive[5].address, configured[5].address);
+        let direct = client.derivepubpath_bitcoind(pub32, "", 7..=7).await.unwrap();
+        assert_eq!(direct[0].path.as_deref(), Some("7"));
+
+        match client.deriveindex(RangeInclusive::new(30, 25)).await {
+            Err(S5Error::Input(_)) => (),
+            other => panic!("expected an input error, got {:?}", other),
+        }
+
+        assert_eq!(gatekeeper.requests().len(), 4);
+        assert_eq!(gatekeeper.requests_to(&routes::DERIVEINDEX)[0].path, "/v0/deriveindex/25-30");
+        assert_eq!(gatekeeper.requests_to(&routes::DERIVEINDEX_BITCOIND)[0].path, "/v0/deriveindex_bitcoind/25-30");
+        let requests = gatekeeper.requests_to(&routes::DERIVEPUBPATH);
+        assert_eq!(requests[0].body, Some(serde_json::json!({ "pub32": pub32, "path": "0/25-30" })));
+        let requests = gatekeeper.requests_to(&routes::DERIVEPUBPATH_BITCOIND);
+        assert_eq!(requests[0].body.as_ref().unwrap()["path"], "7-7");
+    }
+
+    #[tokio::test]
+    async fn mock_ln_node() {
+        let (_gatekeeper, client) = new_client_mock().await;
 
         let lninfo = client.ln_getinfo().await.unwrap();
+        assert_eq!(lninfo.network, "testnet");
         let newaddr = client.ln_newaddr().await.unwrap();
+        assert!(newaddr.bech32.starts_with("tb1"));
         let connstr = client.ln_getconnectionstring().await.unwrap();
+        assert!(connstr.connectstring.starts_with(&lninfo.id));
         let invoice = "lnbc920u1p3khp67pp5mcqxhupukc5te86wfkryerk8f69gg9ptzcep33ry94svm4wvwzqqdqqcqzzgxqyz5vqrzjqwnvuc0u4txn35cafc7w94gxvq5p3cu9dd95f7hlrh0fvs46wpvhdjx4k0kekn630gqqqqryqqqqthqqpyrzjqw8c7yfutqqy3kz8662fxutjvef7q2ujsxtt45csu0k688lkzu3ldjx4k0kekn630gqqqqryqqqqthqqpysp58nxs2nm5wphu234ggawaeul2tnpl6jqc9a0ymfhwpr64vq0k3l4s9qypqsqlkrver3pdxm0teyye0n6y5sje8u90t4j8vpxq3qjwjh9ue46cctj2nzw8fdudfec6nd0e8gx9v485ek7p624j5leeykg70wmv59y3pqqn9ulv2".to_string();
         let bolt11_decoded = client.ln_decodebolt11(invoice).await.unwrap();
+        assert_eq!(bolt11_decoded.min_final_cltv_expiry, 18);
+    }
+
+    #[tokio::test]
+    async fn mock_ln_funds() {
+        let (gatekeeper, client) = new_client_mock().await;
+
         let peer =
             "02eadbd9e7557375161df8b646776a547c5cbc2e95b3071ec81553f8ec2cea3b8c@18.191.253.246:9735"
                 .to_string();
@@ -404,26 +932,552 @@ mod tests {
         let fund_stat = client
             .ln_connectfund(peer, msatoshis, callback_url)
             .await
-            .err();
+            .unwrap();
+        assert_eq!(fund_stat.result, "success");
         let list_funds = client.ln_listfunds().await.unwrap();
+        assert_eq!(list_funds.channels[0].state, "CHANNELD_NORMAL");
+        let withdraw = client
+            .ln_withdraw("tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va", 10_000, "normal")
+            .await
+            .unwrap();
+        assert_eq!(withdraw.txid.len(), 64);
+
+        let requests = gatekeeper.requests_to(&routes::LN_CONNECTFUND);
+        assert_eq!(requests[0].body.as_ref().unwrap()["msatoshi"], 3_690_000);
+    }
+
+    #[tokio::test]
+    async fn mock_ln_peers() {
+        let (_gatekeeper, client) = new_client_mock().await;
+
+        let peer = "02b856473d51e796fc5ff6098afa424d5a35a6e06ce5aa83904a4dcc6f457196d3".to_string();
+        let msatoshis = 3511;
+        let risk_factor = 0.1;
+        let route = client
+            .ln_getroute(peer.clone(), msatoshis, risk_factor)
+            .await
+            .unwrap();
+        assert_eq!(route.route[0].id, peer);
+        assert_eq!(route.route[0].msatoshi, 3511);
+
+        let peers = client.ln_listpeers().await.unwrap().peers;
+        let normal = &peers[0].channels[0];
+        assert_eq!(normal.state, lightning::ChannelState::ChanneldNormal);
+        assert_eq!(normal.to_us(), Some(699_128_000));
+        assert_eq!(normal.spendable(), Some(688_236_000));
+        assert_eq!(normal.our_reserve(), Some(6_997_000));
+        assert_eq!(normal.status.len(), 2);
+        assert_eq!(normal.htlcs[0].amount_msat, Some(13_245_566));
+        let pending = &peers[1].channels[0];
+        assert!(!peers[1].connected);
+        assert_eq!(pending.state, lightning::ChannelState::ChanneldAwaitingLockin);
+        assert_eq!(pending.msatoshi_to_us, None);
+        assert_eq!(pending.to_us(), Some(328_682_000));
+        assert_eq!(pending.their_reserve(), Some(3_286_000));
+        assert_eq!(pending.spendable(), Some(0));
+        let opening = &peers[2].channels[0];
+        assert_eq!(opening.state, lightning::ChannelState::Openingd);
+        assert_eq!(opening.channel_id, None);
+        assert_eq!(opening.funding_txid, None);
+        assert_eq!(opening.to_us(), None);
+    }
+
+    #[tokio::test]
+    async fn mock_ln_invoices() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let invoice = client
+            .ln_create_invoice(InvoiceAmount::Msatoshi(10_000), "inv20", "desc20", Some(900), Some("http://app/callbackLn".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(invoice.id, 1);
+        assert_eq!(invoice.status, lightning::InvoiceStatus::Unpaid);
+        assert_eq!(invoice.msatoshi, InvoiceAmount::Msatoshi(10_000));
+        assert_eq!(invoice.callback_url.as_deref(), Some("http://app/callbackLn"));
+        assert!(invoice.bolt11.starts_with("lntb"));
+        let donation = client.ln_create_invoice(InvoiceAmount::Any, "tip", "tip jar", None, None).await.unwrap();
+        assert_eq!(donation.msatoshi, InvoiceAmount::Any);
+        match client.ln_create_invoice(InvoiceAmount::Any, "duplicate", "again", None, None).await {
+            Err(S5Error::Gatekeeper(message)) => assert!(message.starts_with("Duplicate label")),
+            other => panic!("expected a lightning error, got {:?}", other),
+        }
+
+        let paid_invoice = client.ln_getinvoice("inv20").await.unwrap().unwrap();
+        assert_eq!(paid_invoice.status, lightning::InvoiceStatus::Paid);
+        assert_eq!(paid_invoice.msatoshi_received, Some(10_000));
+        assert_eq!(paid_invoice.pay_index, Some(5));
+        assert_eq!(paid_invoice.paid_at, Some(1552522426));
+        let expired = client.ln_getinvoice("tip").await.unwrap().unwrap();
+        assert_eq!(expired.status, lightning::InvoiceStatus::Expired);
+        assert_eq!(expired.msatoshi, InvoiceAmount::Any);
+        assert_eq!(expired.paid_at, None);
+        assert_eq!(client.ln_getinvoice("nope").await.unwrap(), None);
+        assert_eq!(client.ln_delinvoice("tip").await.unwrap(), expired);
+        match client.ln_delinvoice("nope").await {
+            Err(S5Error::Gatekeeper(message)) => assert_eq!(message, "Unknown invoice"),
+            other => panic!("expected a lightning error, got {:?}", other),
+        }
+
+        let requests = gatekeeper.requests_to(&routes::LN_CREATE_INVOICE);
+        assert_eq!(
+            requests[0].body,
+            Some(serde_json::json!({
+                "msatoshi": 10_000, "label": "inv20", "description": "desc20",
+                "expiry": 900, "callbackUrl": "http://app/callbackLn"
+            }))
+        );
+        assert_eq!(
+            requests[1].body,
+            Some(serde_json::json!({ "msatoshi": "any", "label": "tip", "description": "tip jar" }))
+        );
+        let requests = gatekeeper.requests_to(&routes::LN_DELINVOICE);
+        assert_eq!(requests[0].path, "/v0/ln_delinvoice/tip");
+    }
+
+    #[tokio::test]
+    async fn mock_ln_payments() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        let invoice = client
+            .ln_create_invoice(InvoiceAmount::Msatoshi(10_000), "inv20", "desc20", None, None)
+            .await
+            .unwrap();
+        let paid = client.ln_pay(&invoice.bolt11, Some(10_000), Some("desc20".to_string())).await.unwrap();
+        assert_eq!(paid.status, lightning::PayStatus::Complete);
+        assert_eq!(paid.payment_hash, invoice.payment_hash);
+        assert_eq!(paid.parts, 1);
+        assert_eq!(paid.amount_sent_msat, 10_001);
+        assert!(paid.preimage.is_some());
+        match client.ln_pay(&invoice.bolt11, Some(20_000), None).await {
+            Err(S5Error::Gatekeeper(message)) => assert!(message.starts_with("Expected msatoshi")),
+            other => panic!("expected a rejected payment, got {:?}", other),
+        }
+        match client.ln_pay(&invoice.bolt11, Some(0), None).await {
+            Err(S5Error::Gatekeeper(message)) => assert_eq!(message, "pay rejected"),
+            other => panic!("expected a rejected payment, got {:?}", other),
+        }
         let list_pays = client.ln_listpays().await.unwrap();
+        assert_eq!(list_pays.pays[0].status, Some(lightning::PayStatus::Complete));
+        let by_hash = client.ln_findpay(&invoice.payment_hash).await.unwrap().unwrap();
+        assert_eq!(by_hash.status, Some(lightning::PayStatus::Complete));
+        assert_eq!(by_hash.preimage, paid.preimage);
+        assert_eq!(by_hash.amount_sent_msat, Some(paid.amount_sent_msat));
+        assert_eq!(client.ln_findpay(&invoice.bolt11).await.unwrap(), Some(by_hash));
+        assert_eq!(client.ln_findpay("lnbc1unknown").await.unwrap(), None);
+
+        let requests = gatekeeper.requests_to(&routes::LN_PAY);
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].body.as_ref().unwrap()["expected_msatoshi"], 20_000);
+        assert!(requests[1].body.as_ref().unwrap().get("expected_description").is_none());
+    }
+
+    #[tokio::test]
+    async fn watch_label_is_one_path_segment() {
+        let (gatekeeper, client) = new_client_mock().await;
+        let label = "cold/vault 1";
+        let txns = client.get_txns_by_watchlabel(label, Some(2)).await.unwrap();
+        assert_eq!(txns.label_txns[0].label, label);
+        client.get_unused_addresses_by_watchlabel(label, None).await.unwrap();
+        client.getactivewatchesbylabel(label).await.unwrap();
+        client.unwatchxpubbylabel(label).await.unwrap();
+        client.getbalancebyxpublabel(label).await.unwrap();
+
+        for (route, path) in [
+            (&routes::GET_TXNS_BY_WATCHLABEL, "/v0/get_txns_by_watchlabel/cold%2Fvault%201/2"),
+            (&routes::GET_UNUSED_ADDRESSES_BY_WATCHLABEL, "/v0/get_unused_addresses_by_watchlabel/cold%2Fvault%201"),
+            (&routes::GETACTIVEWATCHESBYLABEL, "/v0/getactivewatchesbylabel/cold%2Fvault%201"),
+            (&routes::UNWATCHXPUBBYLABEL, "/v0/unwatchxpubbylabel/cold%2Fvault%201"),
+            (&routes::GETBALANCEBYXPUBLABEL, "/v0/getbalancebyxpublabel/cold%2Fvault%201"),
+        ] {
+            assert_eq!(gatekeeper.requests_to(route)[0].path, path);
+        }
+    }
+
+    #[test]
+    fn errors_from_status() {
+        let body = || "gatekeeper says no".to_string();
+        assert!(matches!(S5Error::from_status(401, body()), S5Error::Unauthorized(_)));
+        assert!(matches!(S5Error::from_status(403, body()), S5Error::Forbidden(_)));
+        assert!(matches!(S5Error::from_status(404, body()), S5Error::NotFound(_)));
+        let unavailable = S5Error::from_status(503, body());
+        assert!(matches!(unavailable, S5Error::Unavailable { status: 503, .. }));
+        assert!(unavailable.is_transient());
+        let http = S5Error::from_status(500, body());
+        assert!(matches!(http, S5Error::Http { status: 500, .. }));
+        assert!(!http.is_transient());
+        assert!(!S5Error::from_status(504, body()).is_transient());
+        assert!(!S5Error::Interrupted(body()).is_transient());
+        assert_eq!(http.kind(), e::ErrorKind::Internal);
+    }
+
+    #[tokio::test]
+    async fn timeouts_are_not_transient() {
+        // accepts, reads nothing and never answers
+        let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = silent.local_addr().unwrap().to_string();
+        let held = tokio::spawn(async move {
+            let mut open = vec![];
+            while let Ok((tcp, _)) = silent.accept().await {
+                open.push(tcp);
+            }
+        });
+        let builder = || {
+            CnGateway::builder()
+                .scheme("http")
+                .kid("003")
+                .key("00".repeat(32))
+                .request_timeout(Duration::from_millis(200))
+                .spend_timeout(Duration::from_millis(800))
+                .retries(0)
+        };
+        let client = builder().host(&host).build().await.unwrap();
+
+        let error = client.getmempoolinfo().await.unwrap_err();
+        assert!(matches!(error, S5Error::Interrupted(_)));
+        assert!(!error.is_transient());
+        let started = std::time::Instant::now();
+        let error = client.spend("tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va", 0.0001, None, None, None).await.unwrap_err();
+        assert!(matches!(error, S5Error::Interrupted(_)));
+        assert!(started.elapsed() >= Duration::from_millis(800));
+        held.abort();
+
+        // nothing listening: the request never left
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = closed.local_addr().unwrap().to_string();
+        drop(closed);
+        let error = builder().host(&host).build().await.unwrap().getmempoolinfo().await.unwrap_err();
+        assert!(matches!(error, S5Error::Transport(_)));
+        assert!(error.is_transient());
+    }
+
+    #[test]
+    fn errors_do_not_display_bodies() {
+        let body = r#"{"address":"tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va","balance":"lots"}"#;
+        let error = core::Balance::from_str(body).unwrap_err();
+        assert!(matches!(error, S5Error::Deserialize { body: ref kept, .. } if kept == body));
+        assert!(!error.to_string().contains("tb1q"));
+        let error = callback::CallbackEvent::from_str(body).unwrap_err();
+        assert!(!error.to_string().contains("tb1q"));
+    }
+
+    #[tokio::test]
+    async fn invoice_label_is_one_path_segment() {
+        let (gatekeeper, client) = new_client_mock().await;
+        let label = "orders/7082 #1?";
+        assert_eq!(client.ln_getinvoice(label).await.unwrap(), None);
+        let deleted = client.ln_delinvoice(label).await.unwrap();
+        assert_eq!(deleted.label, label);
 
-        // let peer = "02b856473d51e796fc5ff6098afa424d5a35a6e06ce5aa83904a4dcc6f457196d3".to_string();
-        // let msatoshis = 3511;
-        // let risk_factor = 0.1;
-        // let routes = client
-        //     .ln_getroute(peer, msatoshis, risk_factor)
-        //     .await
-        //     .unwrap();
-
-        println!("{:#?}", lninfo);
-        println!("{:#?}", newaddr);
-        println!("{:#?}", connstr);
-        println!("{:#?}", bolt11_decoded);
-        println!("{:#?}", fund_stat);
-        println!("{:#?}", list_funds);
-        println!("{:#?}", list_pays);
-        // println!("{:#?}", routes);
+        let requests = gatekeeper.requests_to(&routes::LN_GETINVOICE);
+        assert_eq!(requests[0].path, "/v0/ln_getinvoice/orders%2F7082%20%231%3F");
+        let requests = gatekeeper.requests_to(&routes::LN_DELINVOICE);
+        assert_eq!(requests[0].path, "/v0/ln_delinvoice/orders%2F7082%20%231%3F");
+    }
+
+    #[tokio::test]
+    async fn every_route_hits_its_method_and_path() {
+        use crate::routes::ROUTES;
+        let (gatekeeper, client) = new_client_mock().await;
+        let address = "tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va";
+        let xpub = "tpubD6NzVbkrYhZ4YR3QK2tyfMMvBghAvqtNaNK1LTyDWcRHLcMUm3ZN2cGm5BS3MhCRCeCkXQkTXXjiJgqxpqXK7PeUSp86DTTgkLpcjMtpKWk";
+        let txid = "b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387";
+        let node = "02b856473d51e796fc5ff6098afa424d5a35a6e06ce5aa83904a4dcc6f457196d3";
+        let callback = "http://app/callback";
+
+        // core
+        client.getmempoolinfo().await.unwrap();
+        client.getbalance().await.unwrap();
+        client.getbalances().await.unwrap();
+        client.getbalancebyxpub(xpub).await.unwrap();
+        client.getbalancebyxpublabel("2219").await.unwrap();
+        client.getnewaddress(AddressType::Bech32, "dup").await.unwrap();
+        client.validateaddress(address).await.unwrap();
+        client.getblockchaininfo().await.unwrap();
+        client.getblockhash(593104).await.unwrap();
+        client.getbestblockhash().await.unwrap();
+        client.getblockinfo("000000006f82a384c208ecfa04d05beea02d420f3f398ddda5c7f900de5718ea").await.unwrap();
+        client.getbestblockinfo().await.unwrap();
+        client.gettransaction("af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648").await.unwrap();
+        client.conf(txid).await.unwrap();
+        client.spend(address, 0.0001, None, None, None).await.unwrap();
+        client.bumpfee(txid, Some(2)).await.unwrap();
+        client.estimatesmartfee(6, None).await.unwrap();
+        client.deriveindex(0..=1).await.unwrap();
+        client.derivepubpath(xpub, "0", 0..=1).await.unwrap();
+        client.deriveindex_bitcoind(0..=1).await.unwrap();
+        client.derivepubpath_bitcoind(xpub, "0", 0..=1).await.unwrap();
+        // watcher
+        client.watch(address, callback, callback, "hot", None).await.unwrap();
+        client.unwatch(address.to_string()).await.unwrap();
+        client.getactivewatches().await.unwrap();
+        client.get_txns_by_watchlabel("hot", None).await.unwrap();
+        client.get_unused_addresses_by_watchlabel("hot", None).await.unwrap();
+        let watch_xpub = client.watchxpub("hot", xpub, "0/n", 0, callback, callback).await.unwrap();
+        assert_eq!(watch_xpub.pub32, xpub);
+        client.unwatchxpubbyxpub(xpub).await.unwrap();
+        client.unwatchxpubbylabel("hot").await.unwrap();
+        client.getactivexpubwatches().await.unwrap();
+        client.getactivewatchesbyxpub(xpub).await.unwrap();
+        client.getactivewatchesbylabel("hot").await.unwrap();
+        client.watchtxid(txid, callback, callback, 6).await.unwrap();
+        client.unwatchtxid(txid, None, None).await.unwrap();
+        // batcher
+        client.createbatcher("fast", 2).await.unwrap();
+        client.updatebatcher(None, Some("5".to_string()), 2).await.unwrap();
+        client.addtobatch(address, 0.0001, "fast", None).await.unwrap();
+        client.removefrombatch(34).await.unwrap();
+        client.getbatcher(None, Some("5".to_string())).await.unwrap();
+        client.getbatchdetails(5, None, None).await.unwrap();
+        client.listbatchers().await.unwrap();
+        client.batchspend(None, Some("5".to_string()), None).await.unwrap();
+        // lightning
+        client.ln_getinfo().await.unwrap();
+        client.ln_newaddr().await.unwrap();
+        client.ln_getconnectionstring().await.unwrap();
+        client.ln_connectfund(format!("{}@127.0.0.1:9735", node), 100_000, callback).await.unwrap();
+        client.ln_decodebolt11("lnbc920u1p3khp67pp5").await.unwrap();
+        client.ln_listfunds().await.unwrap();
+        client.ln_listpays().await.unwrap();
+        client.ln_getroute(node.to_string(), 1000, 0.1).await.unwrap();
+        client.ln_withdraw(address, 10_000, "normal").await.unwrap();
+        client.ln_create_invoice(InvoiceAmount::Msatoshi(1000), "inv", "desc", None, Some(callback.to_string())).await.unwrap();
+        client.ln_pay("lntb100n1pwz", None, None).await.unwrap();
+        client.ln_getinvoice("inv20").await.unwrap();
+        client.ln_delinvoice("tip").await.unwrap();
+        client.ln_listpeers().await.unwrap();
+
+        for route in ROUTES {
+            let hits = gatekeeper.requests_to(route);
+            assert!(!hits.is_empty(), "{} was never called", route.path);
+            for hit in hits {
+                assert_eq!(hit.method, route.method.to_string(), "{}", route.path);
+            }
+        }
+        assert_eq!(gatekeeper.requests().len(), ROUTES.len());
+    }
+
+    #[tokio::test]
+    async fn mock_gatekeeper_statuses() {
+        let (gatekeeper, client) = new_client_mock().await;
+
+        // kid the gatekeeper knows, with the wrong key
+        let stranger = gatekeeper
+            .client_with_keys(KeyRing::single("003".to_string(), "00".repeat(32)))
+            .await
+            .unwrap();
+        assert!(matches!(stranger.getmempoolinfo().await, Err(S5Error::Unauthorized(_))));
+
+        // a single key is used for everything; the gatekeeper rejects what its groups do not allow
+        let stats = gatekeeper.key("000").unwrap();
+        let stats_only = gatekeeper
+            .client_with_keys(KeyRing::single(stats.id, stats.key))
+            .await
+            .unwrap();
+        assert!(stats_only.getmempoolinfo().await.is_ok());
+        assert!(matches!(stats_only.getbalance().await, Err(S5Error::Forbidden(_))));
+
+        // a ring with known groups refuses before sending
+        let sent = gatekeeper.requests().len();
+        let ring = KeyRing::new(vec![gatekeeper.key("000").unwrap()]);
+        let ring_client = gatekeeper.client_with_keys(ring).await.unwrap();
+        assert!(matches!(ring_client.getbalance().await, Err(S5Error::Auth(_))));
+        assert_eq!(gatekeeper.requests().len(), sent);
+
+        // read-only GETs retry through a proxy outage, POSTs are sent once
+        let sent = gatekeeper.requests().len();
+        gatekeeper.fail_next(&[503, 502]);
+        assert_eq!(client.getmempoolinfo().await.unwrap().size, 25);
+        assert_eq!(gatekeeper.requests().len(), sent + 3);
+
+        gatekeeper.fail_next(&[503]);
+        let spend = client.batchspend(Some("default".to_string()), None, None).await;
+        assert!(matches!(spend, Err(S5Error::Unavailable { status: 503, .. })));
+        assert_eq!(gatekeeper.requests().len(), sent + 4);
+
+        // least privileged key per call
+        let mempool = gatekeeper.requests_to(&routes::GETMEMPOOLINFO);
+        assert_eq!(mempool.last().unwrap().kid.as_deref(), Some("000"));
+        assert_eq!(gatekeeper.requests_to(&routes::BATCHSPEND)[0].kid.as_deref(), Some("002"));
+    }
+
+    #[tokio::test]
+    async fn callback_listener() {
+        use crate::callback::{CallbackEvent, CallbackListener};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut listener = CallbackListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr());
+        let zeroconf = r#"{"id":"3832","address":"2NB96fbwy8eoHttuZTtbwvvhEYrBwz494ov","hash":"af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648","vout_n":1,"sent_amount":0.84050318,"confirmations":0,"received":"2018-10-18T15:41:06+0000","size":371,"vsize":166,"fees":0.00002992,"replaceable":false,"blockhash":"","blocktime":"","blockheight":""}"#;
+        let oneconf = r#"{"id":"3832","address":"2NB96fbwy8eoHttuZTtbwvvhEYrBwz494ov","hash":"af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648","vout_n":1,"sent_amount":0.84050318,"confirmations":1,"received":"2018-10-18T15:41:06+0000","size":371,"vsize":166,"fees":0.00002992,"replaceable":false,"blockhash":"00000000000000000011bb83bb9bed0f6e131d0d0c903ec3a063e00b3aa00bf6","blocktime":"2018-10-18T16:58:49+0000","blockheight":"1447392"}"#;
+        let xpub = r#"{"id":"3833","address":"tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va","hash":"af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648","vout_n":0,"sent_amount":0.001,"confirmations":0,"received":"2018-10-18T15:41:06+0000","size":371,"vsize":166,"fees":0.00002992,"replaceable":true,"blockhash":"","blocktime":"","blockheight":"","pub32":"tpubD6NzVbkrYhZ4YR3QK2tyfMMvBghAvqtNaNK1LTyDWcRHLcMUm3ZN2cGm5BS3MhCRCeCkXQkTXXjiJgqxpqXK7PeUSp86DTTgkLpcjMtpKWk","pub32_label":"hot","pub32_derivation_path":"0/n"}"#;
+        let batch = r#"{"outputId":1,"address":"tb1qks9n9440qesu5hvnafc7m2hvuemtynwmwmj2va","amount":0.00003,"batcherId":1,"confTarget":6,"nbOutputs":1,"oldest":"2020-09-09 14:00:01","total":0.00003,"status":"accepted","txid":"af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648","hash":"af867c86000da76df7ddb1054b273ca9e034e8c89d049b5b2795f9f590f67648","details":{"firstseen":1599660001,"size":222,"vsize":141,"replaceable":true,"fee":0.00000141}}"#;
+        let funded = r#"{"result":"success","txid":"85b6f3e8dc4d0e4e2f8b1a4c9b6c1ddc0f1e5a8c7d6b5a4f3e2d1c0b9a8f7e6d","channel_id":"6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e"}"#;
+        let txid = r#"{"id":"5","txid":"b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387","confirmations":6}"#;
+        let paid = r#"{"id":"9","label":"inv20","bolt11":"lntb1pdca82tpp5g0dmzjjjy6grxw3qfx9cm5zrtxk3mvcpmd3lq4gnphr4k2ld8wdsdqqcqzys","callback_url":"http://192.168.111.233:1111/callbackLn","payment_hash":"43dbb14a52269033399049338dd0435d8d1b2c03db63e0551305c7e6cbed3b9b","msatoshi":10000,"status":"paid","pay_index":5,"msatoshi_received":10000,"paid_at":1552522426,"description":"desc20","expires_at":1552526010}"#;
+
+        let http = reqwest::Client::new();
+        for (path, body) in [
+            ("callback0conf", zeroconf),
+            ("callback1conf", oneconf),
+            ("xpub", xpub),
+            ("batch", batch),
+            ("channel", funded),
+            ("invoice", paid),
+            ("callback6conf", txid),
+        ] {
+            let response = http.post(format!("{}/{}", base, path)).body(body).send().await.unwrap();
+            assert_eq!(response.status().as_u16(), 200);
+        }
+        let response = http.post(format!("{}/unknown", base)).body("{}").send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 400);
+        let response = http.post(format!("{}/huge", base)).body(" ".repeat(callback::MAX_BODY + 1)).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 413);
+        // no content-length: the cap applies while reading the chunks
+        let mut tcp = tokio::net::TcpStream::connect(listener.local_addr()).await.unwrap();
+        let chunk = " ".repeat(64 * 1024);
+        let mut chunked = "POST /huge HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n".to_string();
+        for _ in 0..5 {
+            chunked.push_str(&format!("{:x}\r\n{}\r\n", chunk.len(), chunk));
+        }
+        chunked.push_str("0\r\n\r\n");
+        tcp.write_all(chunked.as_bytes()).await.unwrap();
+        let mut status = [0; 12];
+        tcp.read_exact(&mut status).await.unwrap();
+        assert_eq!(&status, b"HTTP/1.1 413");
+
+        let callback = listener.next().await.unwrap();
+        assert_eq!(callback.path, "/callback0conf");
+        assert!(matches!(callback.event, CallbackEvent::AddressZeroConf(ref tx) if tx.blockheight.is_none()));
+        let callback = listener.next().await.unwrap();
+        assert!(matches!(callback.event, CallbackEvent::AddressConfirmed(ref tx) if tx.blockheight == Some(1447392)));
+        let callback = listener.next().await.unwrap();
+        assert!(matches!(callback.event, CallbackEvent::XpubTx(ref tx) if tx.pub32_label.as_deref() == Some("hot")));
+        let callback = listener.next().await.unwrap();
+        assert!(matches!(callback.event, CallbackEvent::BatchSpent(ref spent) if spent.output_id == 1));
+        let callback = listener.next().await.unwrap();
+        assert!(matches!(callback.event, CallbackEvent::ChannelFunded(ref funded) if funded.result == "success"));
+        let callback = listener.next().await.unwrap();
+        assert!(matches!(callback.event, CallbackEvent::InvoicePaid(ref paid) if paid.msatoshi_received == Some(10000) && paid.status == lightning::InvoiceStatus::Paid));
+        let unknown = lightning::LnInvoiceCallback::from_str(&paid.replace(r#""status":"paid""#, r#""status":"refunded""#)).unwrap();
+        assert_eq!(unknown.status, lightning::InvoiceStatus::Unknown);
+        let callback = listener.next().await.unwrap();
+        assert!(matches!(callback.event, CallbackEvent::TxidConfirmed(ref tx) if tx.confirmations == 6));
+    }
+    #[test]
+    fn blockchaininfo_before_0_19() {
+        let legacy = r#"{"chain":"test","blocks":1486864,"headers":1486864,"bestblockhash":"000000000000002fb99d683e64bbfc2b7ad16f9a425cf7be77b481fb1afa363b","difficulty":13971064.71015782,"mediantime":1554149114,"verificationprogress":0.9999994536561675,"initialblockdownload":false,"chainwork":"000000000000000000000000000000000000000000000103ceb57a5896f347ce","size_on_disk":23647567017,"pruned":false,"softforks":[{"id":"bip34","version":2,"reject":{"status":true}}],"bip9_softforks":{"csv":{"status":"active","startTime":1456790400,"timeout":1493596800,"since":770112}},"warnings":"Warning: unknown new rules activated (versionbit 28)"}"#;
+        let chain = core::BlockchainInfo::from_str(legacy).unwrap();
+        assert!(matches!(chain.softforks, core::Softforks::Legacy(ref forks) if forks[0].id == "bip34"));
+        assert_eq!(chain.bip9_softforks["csv"].start_time, 1456790400);
+        assert_eq!(chain.bip9_softforks["csv"].status, core::Bip9Status::Active);
+    }
+    #[test]
+    fn listpays_amounts() {
+        let listpays = r#"{"pays":[{"bolt11":"lntb10u1p0hxxzdpp5l2uqsw2aymrw5ayr2c0eeyu7n2mcqfqy3xdaplr4tzqevfylxaqsdqqcqzpgsp5wpyzxagx6hz2k5k46cp7qk4ycwxhrn9yhyqqgl3ywfd6ar9xx2rq9qy9qsqhhzqhwkvsqqkqrj3m6q7cc3quwx3z7rtl6fw5d9uw4ytwtf0t3wprepcpknwy7nfeqcrtdhkpd8c6n5mydtzkzqyyh0fyts7tcqfqqpqrpw5w7","destination":"029b26c73b2c19ec9bdddeeec97c313670c96b6414ceacae0fb1b3502e490a6cbb","payment_hash":"fab80839dd26c6ea748356739c939e9ab78024048999bd0fc7558819624bf340","status":"complete","created_at":1596030800,"preimage":"a7ef27e9a94d63e4028f35ca4213fd9008227ad86815cd40d3413287d819b145","amount_msat":"1000000msat","amount_sent_msat":"1000012msat","number_of_parts":2},{"bolt11":"lntb1p0hxxzdpp5dl0p4ytk7a7wuy9aur2g7eacdrymw6dpxg0u2hcdnw3gpx3d0drsdqqcqzpgsp5ynxh7pfvdlfu6vqawqyuu6l9tz0nu2wu5ynsjn3xc2f9ggmhjrxs9qy9qsqw4vvqq7zu2whqevk6xvsj4n6jsjmh9g9s9w8n5s3t0g6pvl2y5vkk7xk3j6fhrk3ygktjqyh9h2qnqz0wchy6wlrtwydp0u0c8nkxcqc3qtj3","destination":"02eadbd9e7557375161df8b646776a547c5cbc2e95b3071ec81553f8ec2cea3b8c","payment_hash":"6fde1a9176f77cee10bde0d48f67b868c9b7690992d3bd0bfd25b7228d3af349","status":"failed","created_at":1596031011,"amount_sent_msat":"0msat"},{"bolt11":"lntb20u1p0hxy8spp5cfsye2wpg7rynxjggtgukgmnxa8kqt9v7pmrn4szcgnpxyfyq4qsdqqcqzpgsp5n0mfgqzuk3g9nh0zylc8ngp4w6k2m2u0yfq4p0f3ql4h9jcv2gxs9qy9qsqyh0q6u9ejgvz3vdj0vmwsqmhy3lx2fj6qj2l2wrxpwfu6awd2zmkk8xhjt96f4ayn6mrpyj0cz6c4xvl9h9lv8c0hjvtj4ajyvdkxqcpd6fqsy","payment_hash":"c2604ca9c147864999a4842d1cb237337a7602cacf0763a9d82c261311244154","status":"pending","created_at":1596031102,"amount_msat":"2000000msat","amount_sent_msat":"2000020msat"}]}"#;
+        let listed = lightning::LnListPays::from_str(listpays).unwrap();
+        let pays = &listed.pays;
+        assert_eq!(pays[0].status, Some(lightning::PayStatus::Complete));
+        assert_eq!(pays[0].amount_sent_msat, Some(1_000_012));
+        assert_eq!(pays[1].status, Some(lightning::PayStatus::Failed));
+        assert_eq!(pays[1].amount_sent_msat, Some(0));
+        assert_eq!(pays[2].status, Some(lightning::PayStatus::Pending));
+        assert_eq!(pays[2].amount_sent_msat, Some(2_000_020));
+        let complete = listed.find("fab80839dd26c6ea748356739c939e9ab78024048999bd0fc7558819624bf340").unwrap();
+        assert_eq!(complete.amount_sent_msat, Some(1_000_012));
+    }
+    #[test]
+    fn keys_properties_least_privilege() {
+        let properties = r#"
+kapi_id="000";kapi_key="aaaa";kapi_groups="stats";eval ugroups_${kapi_id}=${kapi_groups};eval ukey_${kapi_id}=${kapi_key}
+kapi_id="001";kapi_key="bbbb";kapi_groups="stats,watcher";eval ugroups_${kapi_id}=${kapi_groups};eval ukey_${kapi_id}=${kapi_key}
+kapi_id="002";kapi_key="cccc";kapi_groups="stats,watcher,spender";eval ugroups_${kapi_id}=${kapi_groups};eval ukey_${kapi_id}=${kapi_key}
+"#;
+        let keys = KeyRing::from_properties(properties).unwrap();
+        assert_eq!(keys.keys.len(), 3);
+        assert_eq!(keys.select("getmempoolinfo").unwrap().id, "000");
+        assert_eq!(keys.select("getactivewatches").unwrap().id, "001");
+        assert_eq!(keys.select("batchspend").unwrap().id, "002");
+        assert!(matches!(keys.select("conf"), Err(S5Error::Auth(_))));
+        assert!(KeyRing::from_properties("kapi_id=\"004\";kapi_groups=\"stats\"").is_err());
+    }
+    #[tokio::test]
+    async fn mock_gatekeeper_checks_exp_in_seconds() {
+        let gatekeeper = MockGatekeeper::start().await.unwrap();
+        let stats = gatekeeper.key("000").unwrap();
+        let http = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(gatekeeper.ca_pem.as_bytes()).unwrap())
+            .build()
+            .unwrap();
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        let status = |exp: u64| {
+            let token = encode(
+                &Header::new(Algorithm::HS256),
+                &serde_json::json!({ "id": stats.id, "exp": exp }),
+                &EncodingKey::from_secret(stats.key.as_bytes()),
+            )
+            .unwrap();
+            let request = http
+                .get(routes::GETMEMPOOLINFO.url(&format!("https://{}/v0", gatekeeper.host)))
+                .bearer_auth(token)
+                .send();
+            async move { request.await.unwrap().status().as_u16() }
+        };
+
+        assert_eq!(status(now + 60).await, 200);
+        assert_eq!(status(now - 60).await, 401);
+    }
+    #[tokio::test]
+    async fn token_per_call() {
+        use jsonwebtoken::{decode, DecodingKey, Validation};
+        let gatekeeper = MockGatekeeper::start().await.unwrap();
+        let stats = gatekeeper.key("000").unwrap();
+        let client = CnGateway::builder()
+            .host(gatekeeper.host.clone())
+            .cert_pem(gatekeeper.ca_pem.clone())
+            .keys(KeyRing::new(vec![stats.clone()]))
+            .token_lifetime(Duration::from_secs(600))
+            .build()
+            .await
+            .unwrap();
+        let claims = |token: String| {
+            decode::<Claims>(&token, &DecodingKey::from_secret(stats.key.as_bytes()), &Validation::new(Algorithm::HS256))
+                .unwrap()
+                .claims
+        };
+
+        let first = claims(client.token(&routes::GETMEMPOOLINFO).unwrap());
+        // exp has a one second resolution
+        tokio::time::sleep(Duration::from_millis(1_100)).await;
+        let second = claims(client.token(&routes::GETMEMPOOLINFO).unwrap());
+        assert_eq!(first.id, "000");
+        assert!(second.exp > first.exp);
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        assert!(second.exp >= now + 599 && second.exp <= now + 600);
+    }
+    #[tokio::test]
+    async fn builder_url_base_path() {
+        for (url, base_url) in [
+            ("https://cyphernode:2009", "https://cyphernode:2009/v0"),
+            ("https://cyphernode:2009/", "https://cyphernode:2009/v0"),
+            ("http://localhost:2009/v1/", "http://localhost:2009/v1"),
+            ("https://gatekeeper/cyphernode/v0", "https://gatekeeper/cyphernode/v0"),
+        ] {
+            let client = CnGateway::builder().url(url).unwrap().kid("003").key("00".repeat(32)).build().await.unwrap();
+            assert_eq!(client.base_url, base_url, "{}", url);
+            assert_eq!(routes::GETBLOCKCHAININFO.url(&client.base_url), format!("{}/getblockchaininfo", base_url));
+        }
+    }
+    #[test]
+    fn debug_redacts_keys() {
+        let secret = "ab".repeat(32);
+        let keys = KeyRing::single("003".to_string(), secret.clone());
+        let builder = CnGateway::builder().kid("003").key(&secret).keys(keys.clone());
+        for debug in [format!("{:?}", keys), format!("{:?}", builder)] {
+            assert!(debug.contains("003"));
+            assert!(debug.contains("<redacted>"));
+            assert!(!debug.contains(&secret));
+        }
+        assert!(format!("{:?}", keys).contains("Admin"));
     }
     #[tokio::test]
     #[ignore]
@@ -433,26 +1487,16 @@ mod tests {
         println!("{:#?}", mempool);
     }
     /*
-    
+
 
     HELPERS
-    
+
 
      */
-    async fn new_client_localhost()->CnGateway{
-        let gatekeeper_ip = "localhost:2009".to_string();
-        let kid = "003".to_string();
-        let key = "c06f9fc30c50ab7541cefaeb58708fe28babcf7d5ed1767a59685f63d0b63c54".to_string();
-        let project_path = env!("CARGO_MANIFEST_DIR");
-        let cert_path = format!("{}/certs/cacert.pem", project_path);
-        CnGateway::new(
-            gatekeeper_ip.clone(),
-            kid.clone(),
-            key.clone(),
-            cert_path.clone(),
-        )
-        .await
-        .unwrap()
+    async fn new_client_mock()->(MockGatekeeper, CnGateway){
+        let gatekeeper = MockGatekeeper::start().await.unwrap();
+        let client = gatekeeper.client().await.unwrap();
+        (gatekeeper, client)
     }
     async fn new_client_cyphernodeappsnet()->CnGateway{
         let gatekeeper_ip = "gatekeeper:2009".to_string();
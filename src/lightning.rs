@@ -1,9 +1,9 @@
-use crate::e::{ErrorKind, S5Error};
-use reqwest::{
-    header::{HeaderMap, AUTHORIZATION},
-    Certificate,
-};
-use serde_derive::{Deserialize, Serialize};
+use crate::de;
+use crate::e::S5Error;
+use crate::http;
+use crate::routes;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 /// Response from <- GET http://cyphernode:8888/ln_getinfo
@@ -21,13 +21,11 @@ pub struct LnInfo {
 }
 impl LnInfo {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnInfo, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -41,32 +39,13 @@ pub struct Binding {
 }
 ///Calls getinfo from lightningd. Useful to let your users know where to connect to.
 pub async fn ln_getinfo(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<LnInfo, String> {
-    let full_url: String = format!("https://{}/v0/ln_getinfo", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnInfo::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+    client: &Client,
+) -> Result<LnInfo, S5Error> {
+    let full_url: String = routes::LN_GETINFO.url(&base_url);
+    let text = http::get(client, &routes::LN_GETINFO, &full_url, jwt).await?;
+    LnInfo::from_str(&text)
 }
 
 /// Response from <- GET http://cyphernode:8888/ln_newaddr.
@@ -77,44 +56,23 @@ pub struct LnFundAddress {
 }
 impl LnFundAddress {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnFundAddress, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 ///Returns a Bitcoin bech32 address to fund your LN wallet.
 pub async fn ln_newaddr(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<LnFundAddress, String> {
-    let full_url: String = format!("https://{}/v0/ln_newaddr", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnFundAddress::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+    client: &Client,
+) -> Result<LnFundAddress, S5Error> {
+    let full_url: String = routes::LN_NEWADDR.url(&base_url);
+    let text = http::get(client, &routes::LN_NEWADDR, &full_url, jwt).await?;
+    LnFundAddress::from_str(&text)
 }
 
 /// Response from <- GET http://cyphernode:8888/ln_getconnectionstring
@@ -124,44 +82,23 @@ pub struct LnConnString {
 }
 impl LnConnString {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnConnString, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 ///Returns a string containing your LN node connection information.
 pub async fn ln_getconnectionstring(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<LnConnString, String> {
-    let full_url: String = format!("https://{}/v0/ln_getconnectionstring", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnConnString::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+    client: &Client,
+) -> Result<LnConnString, S5Error> {
+    let full_url: String = routes::LN_GETCONNECTIONSTRING.url(&base_url);
+    let text = http::get(client, &routes::LN_GETCONNECTIONSTRING, &full_url, jwt).await?;
+    LnConnString::from_str(&text)
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -184,10 +121,7 @@ impl LnConnectFundReq {
     pub fn to_string(&self) -> Result<String, S5Error> {
         match serde_json::to_string(&self.clone()) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::Internal(e.to_string())),
         }
     }
 }
@@ -201,13 +135,11 @@ pub struct LnConnectFund {
 }
 impl LnConnectFund {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnConnectFund, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -218,13 +150,32 @@ pub struct LnConnectFundError {
 }
 impl LnConnectFundError {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnConnectFundError, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+/// Posted by cyphernode to the callbackUrl of ln_connectfund once the channel is funded, or when funding failed
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnConnectFundCallback {
+    pub result: String,
+    #[serde(default)]
+    pub txid: Option<String>,
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+impl LnConnectFundCallback {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnConnectFundCallback, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -232,32 +183,16 @@ impl LnConnectFundError {
 ///Then, it will fund a channel of the provided amount between you two. 
 ///Cyphernode will call the supplied callback URL when the channel is ready to be used.
 pub async fn ln_connectfund(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: LnConnectFundReq,
-) -> Result<LnConnectFund, String> {
-    let full_url: String = format!("https://{}/v0/ln_connectfund", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnConnectFund::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
+) -> Result<LnConnectFund, S5Error> {
+    let full_url: String = routes::LN_CONNECTFUND.url(&base_url);
+    let text = http::post(client, &routes::LN_CONNECTFUND, &full_url, jwt, &body).await?;
+    match LnConnectFundError::from_str(&text) {
+        Ok(failed) => Err(S5Error::Gatekeeper(failed.message)),
+        Err(_) => LnConnectFund::from_str(&text),
     }
 }
 
@@ -276,45 +211,24 @@ pub struct LnBolt11 {
 
 impl LnBolt11 {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnBolt11, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 ///Returns the detailed information of a BOLT11 string of a Lightning Network invoice.
 pub async fn ln_decodebolt11(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     invoice: String,
-) -> Result<LnBolt11, String> {
-    let full_url: String = format!("https://{}/v0/ln_decodebolt11/{}", host, invoice).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnBolt11::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+) -> Result<LnBolt11, S5Error> {
+    let full_url: String = routes::LN_DECODEBOLT11.url_with(&base_url, &[&invoice]);
+    let text = http::get(client, &routes::LN_DECODEBOLT11, &full_url, jwt).await?;
+    LnBolt11::from_str(&text)
 }
 // GET http://cyphernode:8888/ln_listpeers
 /*
@@ -466,9 +380,159 @@ RESPONSE{
    ]
 }
 */
-
-
+/// Response from <- GET http://cyphernode:8888/ln_listpeers
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnListPeers {
+    pub peers: Vec<Peer>,
+}
+impl LnListPeers {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnListPeers, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Peer {
+    pub id: String,
+    pub connected: bool,
+    #[serde(default)]
+    pub netaddr: Vec<String>,
+    #[serde(default)]
+    pub features: Option<String>,
+    #[serde(default)]
+    pub channels: Vec<PeerChannel>,
+}
+/// lightningd channel states
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChannelState {
+    Openingd,
+    ChanneldAwaitingLockin,
+    ChanneldNormal,
+    ChanneldShuttingDown,
+    ClosingdSigexchange,
+    ClosingdComplete,
+    AwaitingUnilateral,
+    FundingSpendSeen,
+    Onchain,
+    Closed,
+    DualopendOpenInit,
+    DualopendAwaitingLockin,
+    /// A state this client does not know yet
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+/// Older lightningd send both msatoshi numbers and "...msat" strings, newer ones only the strings:
+/// read amounts with the methods rather than the fields.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerChannel {
+    pub state: ChannelState,
+    pub scratch_txid: Option<String>,
+    pub owner: Option<String>,
+    pub short_channel_id: Option<String>,
+    pub direction: Option<u8>,
+    /// Missing while the channel is still being opened
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub funding_txid: Option<String>,
+    pub close_to_addr: Option<String>,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub msatoshi_to_us: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub to_us_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub msatoshi_total: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub total_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub spendable_msatoshi: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub spendable_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub receivable_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub their_channel_reserve_satoshis: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub their_reserve_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub our_channel_reserve_satoshis: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub our_reserve_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub dust_limit_msat: Option<u64>,
+    pub their_to_self_delay: Option<u32>,
+    pub our_to_self_delay: Option<u32>,
+    pub max_accepted_htlcs: Option<u32>,
+    /// Latest state changes, eg. "CHANNELD_NORMAL:Reconnected, and reestablished."
+    #[serde(default)]
+    pub status: Vec<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub in_payments_fulfilled: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub in_fulfilled_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub out_payments_fulfilled: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub out_fulfilled_msat: Option<u64>,
+    #[serde(default)]
+    pub htlcs: Vec<Htlc>,
+}
+impl PeerChannel {
+    /// Our side of the channel, msat
+    pub fn to_us(&self) -> Option<u64> {
+        self.to_us_msat.or(self.msatoshi_to_us)
+    }
+    /// Channel capacity, msat
+    pub fn total(&self) -> Option<u64> {
+        self.total_msat.or(self.msatoshi_total)
+    }
+    /// What we can send right now, msat
+    pub fn spendable(&self) -> Option<u64> {
+        self.spendable_msat.or(self.spendable_msatoshi)
+    }
+    /// Reserve we must keep on our side, msat
+    pub fn our_reserve(&self) -> Option<u64> {
+        self.our_reserve_msat
+            .or(self.our_channel_reserve_satoshis.map(|sats| sats * 1000))
+    }
+    /// Reserve the peer must keep on its side, msat
+    pub fn their_reserve(&self) -> Option<u64> {
+        self.their_reserve_msat
+            .or(self.their_channel_reserve_satoshis.map(|sats| sats * 1000))
+    }
+}
+/// A payment in flight through the channel
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Htlc {
+    /// in or out
+    pub direction: String,
+    pub id: u64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub msatoshi: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub amount_msat: Option<u64>,
+    pub expiry: u64,
+    pub payment_hash: String,
+    pub state: String,
+}
 ///Calls listpeers from lightningd. Returns the list of peers and the channels opened with them, even for currently offline peers.
+pub async fn ln_listpeers(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+) -> Result<LnListPeers, S5Error> {
+    let full_url: String = routes::LN_LISTPEERS.url(&base_url);
+    let text = http::get(client, &routes::LN_LISTPEERS, &full_url, jwt).await?;
+    LnListPeers::from_str(&text)
+}
 
 
 /*
@@ -510,13 +574,11 @@ pub struct LnListFunds {
 }
 impl LnListFunds {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnListFunds, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -546,32 +608,13 @@ pub struct Channel {
 }
 ///Calls listfunds from lightningd. Returns the list of unused outputs and funds in open channels
 pub async fn ln_listfunds(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<LnListFunds, String> {
-    let full_url: String = format!("https://{}/v0/ln_listfunds", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnListFunds::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+    client: &Client,
+) -> Result<LnListFunds, S5Error> {
+    let full_url: String = routes::LN_LISTFUNDS.url(&base_url);
+    let text = http::get(client, &routes::LN_LISTFUNDS, &full_url, jwt).await?;
+    LnListFunds::from_str(&text)
 }
 
 /// Response from <- GET http://cyphernode:8888/ln_listpays
@@ -581,51 +624,51 @@ pub struct LnListPays {
 }
 impl LnListPays {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnListPays, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
+impl LnListPays {
+    /// The payment of an invoice, by bolt11 or payment hash: the completed attempt if any, else the latest one
+    pub fn find(&self, bolt11_or_payment_hash: &str) -> Option<&Pay> {
+        let attempts: Vec<&Pay> = self
+            .pays
+            .iter()
+            .filter(|pay| {
+                pay.bolt11.as_deref() == Some(bolt11_or_payment_hash)
+                    || pay.payment_hash.as_deref() == Some(bolt11_or_payment_hash)
+            })
+            .collect();
+        attempts
+            .iter()
+            .find(|pay| pay.status == Some(PayStatus::Complete))
+            .or(attempts.last())
+            .copied()
+    }
+}
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Pay {
     pub bolt11: Option<String>,
-    pub status: Option<String>,
+    #[serde(default)]
+    pub payment_hash: Option<String>,
+    pub status: Option<PayStatus>,
     pub preimage: Option<String>,
-    pub amount_sent_msat: Option<String>,
+    #[serde(default, deserialize_with = "de::option_msat")]
+    pub amount_sent_msat: Option<u64>,
 }
 ///Calls listpays from lightningd. Returns history of paid invoices
 pub async fn ln_listpays(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<LnListPays, String> {
-    let full_url: String = format!("https://{}/v0/ln_listpays", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnListPays::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+    client: &Client,
+) -> Result<LnListPays, S5Error> {
+    let full_url: String = routes::LN_LISTPAYS.url(&base_url);
+    let text = http::get(client, &routes::LN_LISTPAYS, &full_url, jwt).await?;
+    LnListPays::from_str(&text)
 }
 /// Response from <- GET http://cyphernode:8888/ln_getroute/<node_id>/<msatoshi>/<?riskfactor>
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -635,13 +678,11 @@ pub struct LnRoutes {
 }
 impl LnRoutes {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnRoutes, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -656,44 +697,20 @@ pub struct Route {
     pub style: String,
 }
 ///Calls getroute from lightningd. Returns an array representing hops of nodes to get to the destination node from our node
-
 pub async fn ln_getroute(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     node_id: String,
     msatoshis: u128,
     risk_factor: f32,
-) -> Result<LnRoutes, String> {
-    let full_url: String = format!(
-        "https://{}/v0/ln_getroute/{}/{}/{}",
-        host,
-        node_id,
-        msatoshis.to_string(),
-        risk_factor.to_string()
-    )
-    .to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnRoutes::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+) -> Result<LnRoutes, S5Error> {
+    let full_url: String = routes::LN_GETROUTE.url_with(
+        &base_url,
+        &[&node_id, &msatoshis.to_string(), &risk_factor.to_string()],
+    );
+    let text = http::get(client, &routes::LN_GETROUTE, &full_url, jwt).await?;
+    LnRoutes::from_str(&text)
 }
 /// Response from <- POST http://192.168.111.152:8080/ln_withdraw
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -704,13 +721,11 @@ pub struct LnWithdraw {
 }
 impl LnWithdraw {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<LnWithdraw, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -730,7 +745,7 @@ impl LnWithdrawReq {
         LnWithdrawReq {
             destination: address,
             satoshi: amount.to_string(),
-            feerate: feerate,
+            feerate,
             all: false,
         }
     }
@@ -738,42 +753,456 @@ impl LnWithdrawReq {
     pub fn to_string(&self) -> Result<String, S5Error> {
         match serde_json::to_string(&self.clone()) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::Internal(e.to_string())),
         }
     }
 }
 ///Calls withdraw on lightningd with address and payment parameters supplied. 
 ///Withdraws funds to a destination address and Returns the transaction as confirmation.
-
 pub async fn ln_withdraw(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: LnWithdrawReq,
-) -> Result<LnWithdraw, String> {
-    let full_url: String = format!("https://{}/v0/ln_withdraw", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
+) -> Result<LnWithdraw, S5Error> {
+    let full_url: String = routes::LN_WITHDRAW.url(&base_url);
+    let text = http::post(client, &routes::LN_WITHDRAW, &full_url, jwt, &body).await?;
+    LnWithdraw::from_str(&text)
+}
 
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match LnWithdraw::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
+// POST http://cyphernode:8888/ln_create_invoice
+/*
+REQUEST{
+  "msatoshi":10000,
+  "label":"koNCcrSvhX3dmyFhW",
+  "description":"koNCcrSvhX3dmyFhW",
+  "expiry":900,
+  "callbackUrl":"http://192.168.111.233:1111/callbackLN"
+}
+RESPONSE{
+  "id":"1",
+  "label":"koNCcrSvhX3dmyFhW",
+  "bolt11":"lntb100n1pwzllqgpp5m8f9ut3ha6uxfpspz9ng7ckyn8zgxkuerg9w8gkv62dyk4ltqg0sdqjd4kk6mtdypcxj7n6vfshwdqcqzysp6q0jg7ujwllw2ss36a4z3alyzylvlfp3gsnzg75ghtqc2ny8py9x8dj3khq4mxzgmcxfpuyvt4xclgwqz9ym7esqf0aut3sptdyl6cqe9r5gd",
+  "connectstring":"03bb990f43e6a9180a8a8fb27e3fa6ca63e1dff1a4cd14b5f2e5ec4d4dc3cb2f1a@192.168.111.152:9735",
+  "callbackUrl":"http://192.168.111.233:1111/callbackLN",
+  "payment_hash":"fd27edf261d4b089c3478dece4f2c92c8c68db7be3999e89d452d39c083ad00f",
+  "msatoshi":10000,
+  "status":"unpaid",
+  "description":"koNCcrSvhX3dmyFhW",
+  "expires_at":1552528611
+}
+*/
+/// Error from lightning-cli, forwarded as is by the proxy
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnError {
+    pub code: i64,
+    pub message: String,
+}
+impl LnError {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnError, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+/// Invoice amount; Any lets the payer choose
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvoiceAmount {
+    Msatoshi(u64),
+    #[default]
+    Any,
+}
+impl Serialize for InvoiceAmount {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            InvoiceAmount::Msatoshi(msatoshi) => serializer.serialize_u64(*msatoshi),
+            InvoiceAmount::Any => serializer.serialize_str("any"),
+        }
+    }
+}
+impl<'de> Deserialize<'de> for InvoiceAmount {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Number(msatoshi) => match msatoshi.as_u64() {
+                Some(msatoshi) => Ok(InvoiceAmount::Msatoshi(msatoshi)),
+                None => Err(serde::de::Error::custom(format!("invalid msatoshi {}", msatoshi))),
+            },
+            Value::String(msatoshi) if msatoshi == "any" || msatoshi.is_empty() => Ok(InvoiceAmount::Any),
+            Value::String(msatoshi) => match msatoshi.parse::<u64>() {
+                Ok(msatoshi) => Ok(InvoiceAmount::Msatoshi(msatoshi)),
+                Err(e) => Err(serde::de::Error::custom(e)),
+            },
+            Value::Null => Ok(InvoiceAmount::Any),
+            other => Err(serde::de::Error::custom(format!("invalid msatoshi {}", other))),
+        }
+    }
+}
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvoiceStatus {
+    #[default]
+    Unpaid,
+    Paid,
+    Expired,
+    /// A status this client does not know yet
+    #[serde(other)]
+    Unknown,
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnCreateInvoiceReq {
+    pub msatoshi: InvoiceAmount,
+    pub label: String,
+    pub description: String,
+    /// Seconds, lightningd defaults to a week
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<u64>,
+    #[serde(rename = "callbackUrl", skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+}
+impl LnCreateInvoiceReq {
+    pub fn new(
+        msatoshi: InvoiceAmount,
+        label: String,
+        description: String,
+        expiry: Option<u64>,
+        callback_url: Option<String>,
+    ) -> Self {
+        LnCreateInvoiceReq {
+            msatoshi,
+            label,
+            description,
+            expiry,
+            callback_url,
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnInvoice {
+    #[serde(deserialize_with = "de::number")]
+    pub id: u64,
+    pub label: String,
+    pub bolt11: String,
+    #[serde(default)]
+    pub connectstring: Option<String>,
+    #[serde(rename = "callbackUrl", default)]
+    pub callback_url: Option<String>,
+    pub payment_hash: String,
+    #[serde(default)]
+    pub msatoshi: InvoiceAmount,
+    pub status: InvoiceStatus,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(deserialize_with = "de::number")]
+    pub expires_at: u64,
+}
+impl LnInvoice {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnInvoice, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+///Creates a bolt11 invoice. Cyphernode will call callbackUrl once it is paid.
+pub async fn ln_create_invoice(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    body: LnCreateInvoiceReq,
+) -> Result<LnInvoice, S5Error> {
+    let full_url: String = routes::LN_CREATE_INVOICE.url(&base_url);
+    let text = http::post(client, &routes::LN_CREATE_INVOICE, &full_url, jwt, &body).await?;
+    match LnError::from_str(&text) {
+        Ok(failed) => Err(S5Error::Gatekeeper(failed.message)),
+        Err(_) => LnInvoice::from_str(&text),
+    }
+}
+
+// POST http://cyphernode:8888/ln_pay
+/*
+REQUEST{
+  "bolt11":"lntb1pdca82tpp5gv8mn5jqlj6xztpnt4r472zcyrwf3y2c3cvm4uzg2gqcnj90f83qdp2gf5hgcm0d9hzqnm4w3kx2apqdaexgetjyq3nwvpcxgcqp2g3d86wwdfvyxcz7kce7d3n26d2rw3wf5tzpm2m5fl2z3mm8msa3xk8nv2y32gmzlhwjved980mcmkgq83u9wafq9n4w28amnmwzujgqpmapcr3",
+  "expected_msatoshi":10000,
+  "expected_description":"Bitcoin Outlet order #7082"
+}
+RESPONSE{
+  "destination":"029b26c73b2c19ec9bdddeeec97c313670c96b6414ceacae0fb1b3502e490a6cbb",
+  "payment_hash":"430fb9d240fcb4612c335d475f2858dc98915888e19bbaf04852018dc8af49e2",
+  "created_at":1552527869.0,
+  "parts":1,
+  "msatoshi":10000,
+  "amount_msat":"10000msat",
+  "msatoshi_sent":10000,
+  "amount_sent_msat":"10000msat",
+  "payment_preimage":"a7ef27e9a94d63e4028f35ca4213fd9008227ad86815cd40d3413287d819b145",
+  "status":"complete"
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnPayReq {
+    pub bolt11: String,
+    /// Refuse to pay if the invoice asks for a different amount
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_msatoshi: Option<u64>,
+    /// Refuse to pay if the invoice has a different description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_description: Option<String>,
+}
+impl LnPayReq {
+    pub fn new(bolt11: String, expected_msatoshi: Option<u64>, expected_description: Option<String>) -> Self {
+        LnPayReq {
+            bolt11,
+            expected_msatoshi,
+            expected_description,
+        }
+    }
+}
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayStatus {
+    Complete,
+    #[default]
+    Pending,
+    Failed,
+    /// A status this client does not know yet
+    #[serde(other)]
+    Unknown,
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnPayment {
+    pub destination: Option<String>,
+    pub payment_hash: String,
+    pub created_at: f64,
+    /// Number of htlcs the payment was split into
+    #[serde(default)]
+    pub parts: u32,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub msatoshi: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub msatoshi_sent: Option<u64>,
+    /// The amount plus fees
+    #[serde(deserialize_with = "de::msat")]
+    pub amount_sent_msat: u64,
+    #[serde(rename = "payment_preimage")]
+    pub preimage: Option<String>,
+    pub status: PayStatus,
+}
+impl LnPayment {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnPayment, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+/// ln_pay refusing an invoice that does not match expected_msatoshi or expected_description
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnPayRejected {
+    pub result: String,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub expected_msatoshi: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub invoice_msatoshi: Option<u64>,
+    #[serde(default)]
+    pub expected_description: Option<String>,
+    #[serde(default)]
+    pub invoice_description: Option<String>,
+}
+impl LnPayRejected {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnPayRejected, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+///Pays a bolt11 invoice, after checking it against the expected amount and description when given.
+pub async fn ln_pay(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    body: LnPayReq,
+) -> Result<LnPayment, S5Error> {
+    let full_url: String = routes::LN_PAY.url(&base_url);
+    let text = http::post(client, &routes::LN_PAY, &full_url, jwt, &body).await?;
+    if let Ok(failed) = LnError::from_str(&text) {
+        return Err(S5Error::Gatekeeper(failed.message));
+    }
+    match LnPayRejected::from_str(&text) {
+        Ok(rejected) if rejected.result == "error" => Err(S5Error::Gatekeeper(rejected.message.unwrap_or_else(|| "pay rejected".to_string()))),
+        _ => LnPayment::from_str(&text),
+    }
+}
+
+// GET http://cyphernode:8888/ln_getinvoice/koNCcrSvhX3dmyFhW
+/*
+RESPONSE{
+  "invoices":[
+    {
+      "label":"koNCcrSvhX3dmyFhW",
+      "bolt11":"lntb100n1pwzllqgpp5m8f9ut3ha6uxfpspz9ng7ckyn8zgxkuerg9w8gkv62dyk4ltqg0sdqjd4kk6mtdypcxj7n6vfshwdqcqzysp6q0jg7ujwllw2ss36a4z3alyzylvlfp3gsnzg75ghtqc2ny8py9x8dj3khq4mxzgmcxfpuyvt4xclgwqz9ym7esqf0aut3sptdyl6cqe9r5gd",
+      "payment_hash":"d9d25e2e37eeb864860111668f62c499c4835b991a0ae3a2cd34a4b557eb021f",
+      "msatoshi":10000,
+      "amount_msat":"10000msat",
+      "status":"paid",
+      "pay_index":1,
+      "msatoshi_received":10000,
+      "amount_received_msat":"10000msat",
+      "paid_at":1552527869,
+      "payment_preimage":"a7ef27e9a94d63e4028f35ca4213fd9008227ad86815cd40d3413287d819b145",
+      "description":"koNCcrSvhX3dmyFhW",
+      "expires_at":1552528611
+    }
+  ]
+}
+*/
+// GET http://cyphernode:8888/ln_delinvoice/koNCcrSvhX3dmyFhW
+// deletes the invoice and returns it
+/*
+RESPONSE{
+  "label":"koNCcrSvhX3dmyFhW",
+  "bolt11":"lntb100n1pwzllqgpp5m8f9ut3ha6uxfpspz9ng7ckyn8zgxkuerg9w8gkv62dyk4ltqg0sdqjd4kk6mtdypcxj7n6vfshwdqcqzysp6q0jg7ujwllw2ss36a4z3alyzylvlfp3gsnzg75ghtqc2ny8py9x8dj3khq4mxzgmcxfpuyvt4xclgwqz9ym7esqf0aut3sptdyl6cqe9r5gd",
+  "payment_hash":"d9d25e2e37eeb864860111668f62c499c4835b991a0ae3a2cd34a4b557eb021f",
+  "msatoshi":10000,
+  "amount_msat":"10000msat",
+  "status":"unpaid",
+  "description":"koNCcrSvhX3dmyFhW",
+  "expires_at":1552528611
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnInvoices {
+    pub invoices: Vec<LnInvoiceStatus>,
+}
+impl LnInvoices {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnInvoices, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+/// An invoice as lightningd knows it; the payment fields are only set once paid
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnInvoiceStatus {
+    pub label: String,
+    #[serde(default)]
+    pub bolt11: Option<String>,
+    pub payment_hash: String,
+    #[serde(default)]
+    pub msatoshi: InvoiceAmount,
+    pub status: InvoiceStatus,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pay_index: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub msatoshi_received: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub paid_at: Option<u64>,
+    #[serde(default)]
+    pub payment_preimage: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(deserialize_with = "de::number")]
+    pub expires_at: u64,
+}
+impl LnInvoiceStatus {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnInvoiceStatus, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+///Returns the invoice created with label, None if there is none.
+pub async fn ln_getinvoice(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    label: String,
+) -> Result<Option<LnInvoiceStatus>, S5Error> {
+    let full_url: String = routes::LN_GETINVOICE.url_with(&base_url, &[&label]);
+    let text = http::get(client, &routes::LN_GETINVOICE, &full_url, jwt).await?;
+    let invoices = LnInvoices::from_str(&text)?;
+    Ok(invoices.invoices.into_iter().find(|invoice| invoice.label == label))
+}
+///Deletes the invoice created with label and returns it.
+pub async fn ln_delinvoice(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    label: String,
+) -> Result<LnInvoiceStatus, S5Error> {
+    let full_url: String = routes::LN_DELINVOICE.url_with(&base_url, &[&label]);
+    let text = http::get(client, &routes::LN_DELINVOICE, &full_url, jwt).await?;
+    match LnError::from_str(&text) {
+        Ok(failed) => Err(S5Error::Gatekeeper(failed.message)),
+        Err(_) => LnInvoiceStatus::from_str(&text),
+    }
+}
+
+// POST callbackUrl of ln_create_invoice
+/*
+CALLBACK{
+  "id":"9",
+  "label":"inv20",
+  "bolt11":"lntb1pdca82tpp5g0dmzjjjy6grxw3qfx9cm5zrtxk3mvcpmd3lq4gnphr4k2ld8wdsdqqcqzys0rcjcn6xgfh7l2thc5a9aq0dx6fc4ufq2tprq5v3pa5mtfrq3yah3ek3qwrtpnjvl5ys3rnp4fs8fjutmhq5yyy57n0ejf4e8e9aflspqtm3xn",
+  "callback_url":"http://192.168.111.233:1111/callbackLn",
+  "payment_hash":"43dbb14a52269033399049338dd0435d8d1b2c03db63e0551305c7e6cbed3b9b",
+  "msatoshi":10000,
+  "status":"paid",
+  "pay_index":5,
+  "msatoshi_received":10000,
+  "paid_at":1552522426,
+  "description":"desc20",
+  "expires_at":1552526010
+}
+*/
+/// Posted by cyphernode to the callbackUrl of an invoice once it is paid
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LnInvoiceCallback {
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub id: Option<u64>,
+    pub label: String,
+    pub bolt11: String,
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    pub payment_hash: String,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub msatoshi: Option<u64>,
+    pub status: InvoiceStatus,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pay_index: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub msatoshi_received: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub paid_at: Option<u64>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub expires_at: Option<u64>,
+}
+impl LnInvoiceCallback {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<LnInvoiceCallback, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
     }
 }
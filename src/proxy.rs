@@ -1,5 +1,5 @@
 use serde_derive::{Deserialize,Serialize};
-use crate::e::{ErrorKind, S5Error};
+use crate::e::S5Error;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProxyHello {
@@ -9,22 +9,19 @@ impl ProxyHello {
     pub fn from_str(stringified: &str) -> Result<ProxyHello, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 
-pub async fn helloworld(ip: String) -> Result<(), String> {
+pub async fn helloworld(ip: String) -> Result<(), S5Error> {
     let full_url: String = format!("http://{}:8888/helloworld", ip).to_string();
 
     match ureq::get(&full_url).call() {
         Ok(response) => match ProxyHello::from_str(&response.into_string().unwrap()) {
             Ok(_) => Ok(()),
-            Err(e) => Err(e.message),
+            Err(e) => Err(e),
         },
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(S5Error::Transport(e.to_string())),
     }
 }
\ No newline at end of file
@@ -0,0 +1,195 @@
+// Every gatekeeper endpoint the client calls, with its http method and the api group allowed to call it
+// (cyphernode/gatekeeper/api.properties). Anything missing here is assumed to need admin.
+use crate::keys::ApiGroup;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Get,
+    Post,
+}
+impl Display for Method {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Method::Get => write!(f, "GET"),
+            Method::Post => write!(f, "POST"),
+        }
+    }
+}
+
+/// One gatekeeper endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Route {
+    pub method: Method,
+    /// First path segment after the base path, eg. watchxpub
+    pub path: &'static str,
+    pub group: ApiGroup,
+}
+impl Route {
+    const fn get(path: &'static str, group: ApiGroup) -> Route {
+        Route {
+            method: Method::Get,
+            path,
+            group,
+        }
+    }
+    const fn post(path: &'static str, group: ApiGroup) -> Route {
+        Route {
+            method: Method::Post,
+            path,
+            group,
+        }
+    }
+    /// base_url/path
+    pub fn url(&self, base_url: &str) -> String {
+        format!("{}/{}", base_url, self.path)
+    }
+    /// base_url/path/param/..., each param percent-encoded into a single path segment
+    pub fn url_with(&self, base_url: &str, params: &[&str]) -> String {
+        let mut url = self.url(base_url);
+        for param in params {
+            url.push('/');
+            url.push_str(&encode_segment(param));
+        }
+        url
+    }
+}
+
+/// Keeps RFC 3986 unreserved characters, percent-encodes every other byte
+fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// core
+pub const GETMEMPOOLINFO: Route = Route::get("getmempoolinfo", ApiGroup::Stats);
+pub const GETBALANCE: Route = Route::get("getbalance", ApiGroup::Spender);
+pub const GETBALANCES: Route = Route::get("getbalances", ApiGroup::Spender);
+pub const GETBALANCEBYXPUB: Route = Route::get("getbalancebyxpub", ApiGroup::Spender);
+pub const GETBALANCEBYXPUBLABEL: Route = Route::get("getbalancebyxpublabel", ApiGroup::Spender);
+pub const GETNEWADDRESS: Route = Route::post("getnewaddress", ApiGroup::Spender);
+pub const VALIDATEADDRESS: Route = Route::get("validateaddress", ApiGroup::Watcher);
+pub const GETBLOCKCHAININFO: Route = Route::get("getblockchaininfo", ApiGroup::Stats);
+pub const GETBLOCKHASH: Route = Route::get("getblockhash", ApiGroup::Stats);
+pub const GETBESTBLOCKHASH: Route = Route::get("getbestblockhash", ApiGroup::Stats);
+pub const GETBLOCKINFO: Route = Route::get("getblockinfo", ApiGroup::Stats);
+pub const GETBESTBLOCKINFO: Route = Route::get("getbestblockinfo", ApiGroup::Stats);
+pub const GETTRANSACTION: Route = Route::get("gettransaction", ApiGroup::Watcher);
+pub const SPEND: Route = Route::post("spend", ApiGroup::Spender);
+pub const BUMPFEE: Route = Route::post("bumpfee", ApiGroup::Spender);
+pub const BITCOIN_ESTIMATESMARTFEE: Route = Route::post("bitcoin_estimatesmartfee", ApiGroup::Watcher);
+// internal in the stock api.properties, so only a gatekeeper configured to expose it will accept it
+pub const CONF: Route = Route::get("conf", ApiGroup::Admin);
+// watcher
+pub const WATCH: Route = Route::post("watch", ApiGroup::Watcher);
+pub const UNWATCH: Route = Route::get("unwatch", ApiGroup::Watcher);
+pub const GETACTIVEWATCHES: Route = Route::get("getactivewatches", ApiGroup::Watcher);
+pub const GET_TXNS_BY_WATCHLABEL: Route = Route::get("get_txns_by_watchlabel", ApiGroup::Watcher);
+pub const GET_UNUSED_ADDRESSES_BY_WATCHLABEL: Route =
+    Route::get("get_unused_addresses_by_watchlabel", ApiGroup::Watcher);
+pub const WATCHXPUB: Route = Route::post("watchxpub", ApiGroup::Watcher);
+pub const UNWATCHXPUBBYXPUB: Route = Route::get("unwatchxpubbyxpub", ApiGroup::Watcher);
+pub const UNWATCHXPUBBYLABEL: Route = Route::get("unwatchxpubbylabel", ApiGroup::Watcher);
+pub const GETACTIVEXPUBWATCHES: Route = Route::get("getactivexpubwatches", ApiGroup::Watcher);
+pub const GETACTIVEWATCHESBYXPUB: Route = Route::get("getactivewatchesbyxpub", ApiGroup::Watcher);
+pub const GETACTIVEWATCHESBYLABEL: Route = Route::get("getactivewatchesbylabel", ApiGroup::Watcher);
+pub const WATCHTXID: Route = Route::post("watchtxid", ApiGroup::Watcher);
+pub const UNWATCHTXID: Route = Route::post("unwatchtxid", ApiGroup::Watcher);
+pub const DERIVEINDEX: Route = Route::get("deriveindex", ApiGroup::Watcher);
+pub const DERIVEPUBPATH: Route = Route::post("derivepubpath", ApiGroup::Watcher);
+pub const DERIVEINDEX_BITCOIND: Route = Route::get("deriveindex_bitcoind", ApiGroup::Watcher);
+pub const DERIVEPUBPATH_BITCOIND: Route = Route::post("derivepubpath_bitcoind", ApiGroup::Watcher);
+// batcher
+pub const CREATEBATCHER: Route = Route::post("createbatcher", ApiGroup::Spender);
+pub const UPDATEBATCHER: Route = Route::post("updatebatcher", ApiGroup::Spender);
+pub const ADDTOBATCH: Route = Route::post("addtobatch", ApiGroup::Spender);
+pub const REMOVEFROMBATCH: Route = Route::post("removefrombatch", ApiGroup::Spender);
+pub const GETBATCHER: Route = Route::post("getbatcher", ApiGroup::Spender);
+pub const GETBATCHDETAILS: Route = Route::post("getbatchdetails", ApiGroup::Spender);
+pub const LISTBATCHERS: Route = Route::get("listbatchers", ApiGroup::Spender);
+pub const BATCHSPEND: Route = Route::post("batchspend", ApiGroup::Spender);
+// lightning
+pub const LN_GETINFO: Route = Route::get("ln_getinfo", ApiGroup::Watcher);
+pub const LN_NEWADDR: Route = Route::get("ln_newaddr", ApiGroup::Spender);
+pub const LN_GETCONNECTIONSTRING: Route = Route::get("ln_getconnectionstring", ApiGroup::Watcher);
+pub const LN_CONNECTFUND: Route = Route::post("ln_connectfund", ApiGroup::Spender);
+pub const LN_DECODEBOLT11: Route = Route::get("ln_decodebolt11", ApiGroup::Watcher);
+pub const LN_LISTFUNDS: Route = Route::get("ln_listfunds", ApiGroup::Spender);
+pub const LN_LISTPAYS: Route = Route::get("ln_listpays", ApiGroup::Watcher);
+pub const LN_GETROUTE: Route = Route::get("ln_getroute", ApiGroup::Watcher);
+pub const LN_WITHDRAW: Route = Route::post("ln_withdraw", ApiGroup::Spender);
+pub const LN_CREATE_INVOICE: Route = Route::post("ln_create_invoice", ApiGroup::Watcher);
+pub const LN_PAY: Route = Route::post("ln_pay", ApiGroup::Spender);
+pub const LN_GETINVOICE: Route = Route::get("ln_getinvoice", ApiGroup::Watcher);
+pub const LN_DELINVOICE: Route = Route::get("ln_delinvoice", ApiGroup::Spender);
+pub const LN_LISTPEERS: Route = Route::get("ln_listpeers", ApiGroup::Watcher);
+
+pub const ROUTES: &[Route] = &[
+    GETMEMPOOLINFO,
+    GETBALANCE,
+    GETBALANCES,
+    GETBALANCEBYXPUB,
+    GETBALANCEBYXPUBLABEL,
+    GETNEWADDRESS,
+    VALIDATEADDRESS,
+    GETBLOCKCHAININFO,
+    GETBLOCKHASH,
+    GETBESTBLOCKHASH,
+    GETBLOCKINFO,
+    GETBESTBLOCKINFO,
+    GETTRANSACTION,
+    SPEND,
+    BUMPFEE,
+    BITCOIN_ESTIMATESMARTFEE,
+    CONF,
+    WATCH,
+    UNWATCH,
+    GETACTIVEWATCHES,
+    GET_TXNS_BY_WATCHLABEL,
+    GET_UNUSED_ADDRESSES_BY_WATCHLABEL,
+    WATCHXPUB,
+    UNWATCHXPUBBYXPUB,
+    UNWATCHXPUBBYLABEL,
+    GETACTIVEXPUBWATCHES,
+    GETACTIVEWATCHESBYXPUB,
+    GETACTIVEWATCHESBYLABEL,
+    WATCHTXID,
+    UNWATCHTXID,
+    DERIVEINDEX,
+    DERIVEPUBPATH,
+    DERIVEINDEX_BITCOIND,
+    DERIVEPUBPATH_BITCOIND,
+    CREATEBATCHER,
+    UPDATEBATCHER,
+    ADDTOBATCH,
+    REMOVEFROMBATCH,
+    GETBATCHER,
+    GETBATCHDETAILS,
+    LISTBATCHERS,
+    BATCHSPEND,
+    LN_GETINFO,
+    LN_NEWADDR,
+    LN_GETCONNECTIONSTRING,
+    LN_CONNECTFUND,
+    LN_DECODEBOLT11,
+    LN_LISTFUNDS,
+    LN_LISTPAYS,
+    LN_GETROUTE,
+    LN_WITHDRAW,
+    LN_CREATE_INVOICE,
+    LN_PAY,
+    LN_GETINVOICE,
+    LN_DELINVOICE,
+    LN_LISTPEERS,
+];
+
+/// The route for an endpoint name, if the client knows it
+pub fn route(path: &str) -> Option<&'static Route> {
+    ROUTES.iter().find(|route| route.path == path)
+}
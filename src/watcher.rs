@@ -1,10 +1,10 @@
-use crate::e::{ErrorKind, S5Error};
-use reqwest::{
-    self,
-    header::{HeaderMap, AUTHORIZATION},
-    Certificate,
-};
+use crate::de;
+use crate::e::S5Error;
+use crate::http;
+use crate::routes;
+use reqwest::Client;
 use serde_derive::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
 
 // POST http://cyphernode/watch
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -39,10 +39,7 @@ impl WatchAddressReq {
     pub fn stringify(&self) -> Result<String, S5Error> {
         match serde_json::to_string(&self.clone()) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::Internal(e.to_string())),
         }
     }
 }
@@ -61,46 +58,187 @@ pub struct WatchAddress {
 
 impl WatchAddress {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<WatchAddress, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 
 pub async fn watch(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: WatchAddressReq,
-) -> Result<WatchAddress, String> {
-    let full_url: String = format!("https://{}/v0/watch", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
+) -> Result<WatchAddress, S5Error> {
+    let full_url: String = routes::WATCH.url(&base_url);
+    let text = http::post(client, &routes::WATCH, &full_url, jwt, &body).await?;
+    WatchAddress::from_str(&text)
+}
+// POST http://cyphernode/watchtxid
+/*
+REQUEST{
+  "txid":"b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387",
+  "confirmedCallbackURL":"192.168.111.233:1111/callback1conf",
+  "xconfCallbackURL":"192.168.111.233:1111/callbackXconf",
+  "nbxconf":6
+}
+RESPONSE{
+  "id":"5",
+  "event":"watchtxid",
+  "inserted":"1",
+  "txid":"b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387",
+  "confirmedCallbackURL":"192.168.111.233:1111/callback1conf",
+  "xconfCallbackURL":"192.168.111.233:1111/callbackXconf",
+  "nbxconf":6
+}
+CALLBACK{
+  "id":"5",
+  "txid":"b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387",
+  "confirmations":6
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchTxidReq {
+    pub txid: String,
+    #[serde(rename = "confirmedCallbackURL")]
+    pub confirmed_callback_url: String,
+    #[serde(rename = "xconfCallbackURL")]
+    pub xconf_callback_url: String,
+    pub nbxconf: u32,
+}
+impl WatchTxidReq {
+    pub fn new(
+        txid: String,
+        confirmed_callback_url: String,
+        xconf_callback_url: String,
+        nbxconf: u32,
+    ) -> Self {
+        WatchTxidReq {
+            txid,
+            confirmed_callback_url,
+            xconf_callback_url,
+            nbxconf,
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchTxid {
+    #[serde(deserialize_with = "de::number")]
+    pub id: u64,
+    pub event: String,
+    #[serde(default, deserialize_with = "de::flag")]
+    pub inserted: bool,
+    pub txid: String,
+    #[serde(rename = "confirmedCallbackURL")]
+    pub confirmed_callback_url: Option<String>,
+    #[serde(rename = "xconfCallbackURL")]
+    pub xconf_callback_url: Option<String>,
+    #[serde(deserialize_with = "de::number")]
+    pub nbxconf: u32,
+}
+impl WatchTxid {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<WatchTxid, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+/// Posted by cyphernode to the confirmedCallbackURL (1 conf) and xconfCallbackURL (nbxconf confs) of a watched txid
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchTxidCallback {
+    #[serde(deserialize_with = "de::number")]
+    pub id: u64,
+    pub txid: String,
+    #[serde(deserialize_with = "de::number")]
+    pub confirmations: u64,
+}
+impl WatchTxidCallback {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<WatchTxidCallback, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+/// Watches a txid: confirmedCallbackURL is called at 1 confirmation and xconfCallbackURL at nbxconf confirmations
+pub async fn watchtxid(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    body: WatchTxidReq,
+) -> Result<WatchTxid, S5Error> {
+    let full_url: String = routes::WATCHTXID.url(&base_url);
+    let text = http::post(client, &routes::WATCHTXID, &full_url, jwt, &body).await?;
+    WatchTxid::from_str(&text)
+}
 
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match WatchAddress::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
+// POST http://cyphernode/unwatchtxid
+/*
+REQUEST{
+  "txid":"b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387",
+  "confirmedCallbackURL":"192.168.111.233:1111/callback1conf",
+  "xconfCallbackURL":"192.168.111.233:1111/callbackXconf"
+}
+RESPONSE{
+  "event":"unwatchtxid",
+  "txid":"b081ca7724386f549cf0c16f71db6affeb52ff7a0d9b606fb2e5c43faffd3387"
+}
+*/
+/// Without callback urls every watch on the txid is removed
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnwatchTxidReq {
+    pub txid: String,
+    #[serde(rename = "confirmedCallbackURL", skip_serializing_if = "Option::is_none")]
+    pub confirmed_callback_url: Option<String>,
+    #[serde(rename = "xconfCallbackURL", skip_serializing_if = "Option::is_none")]
+    pub xconf_callback_url: Option<String>,
+}
+impl UnwatchTxidReq {
+    pub fn new(
+        txid: String,
+        confirmed_callback_url: Option<String>,
+        xconf_callback_url: Option<String>,
+    ) -> Self {
+        UnwatchTxidReq {
+            txid,
+            confirmed_callback_url,
+            xconf_callback_url,
+        }
     }
 }
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnwatchTxid {
+    pub event: String,
+    pub txid: String,
+}
+impl UnwatchTxid {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<UnwatchTxid, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+pub async fn unwatchtxid(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    body: UnwatchTxidReq,
+) -> Result<UnwatchTxid, S5Error> {
+    let full_url: String = routes::UNWATCHTXID.url(&base_url);
+    let text = http::post(client, &routes::UNWATCHTXID, &full_url, jwt, &body).await?;
+    UnwatchTxid::from_str(&text)
+}
 // GET http://cyphernode/getactivewatches
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -109,13 +247,11 @@ pub struct ActiveWatches {
 }
 impl ActiveWatches {
     /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(stringified: &str) -> Result<ActiveWatches, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
@@ -135,31 +271,13 @@ pub struct Watch {
 }
 
 pub async fn getactivewatches(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
-) -> Result<ActiveWatches, String> {
-    let full_url: String = format!("https://{}/v0/getactivewatches", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                match ActiveWatches::from_str(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+    client: &Client,
+) -> Result<ActiveWatches, S5Error> {
+    let full_url: String = routes::GETACTIVEWATCHES.url(&base_url);
+    let text = http::get(client, &routes::GETACTIVEWATCHES, &full_url, jwt).await?;
+    ActiveWatches::from_str(&text)
 }
 // GET http://cyphernode/unwatch/2N8DcqzfkYi8CkYzvNNS5amoq3SbAcQNXKp
 /*
@@ -184,41 +302,19 @@ impl UnwatchAddress {
     pub fn structify(stringified: &str) -> Result<UnwatchAddress, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 pub async fn unwatch(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     address: String,
-) -> Result<UnwatchAddress, String> {
-    let full_url: String = format!("https://{}/v0/unwatch/{}", host, address).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match UnwatchAddress::structify(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+) -> Result<UnwatchAddress, S5Error> {
+    let full_url: String = routes::UNWATCH.url_with(&base_url, &[&address]);
+    let text = http::get(client, &routes::UNWATCH, &full_url, jwt).await?;
+    UnwatchAddress::structify(&text)
 }
 // GET http://cyphernode/get_txns_by_watchlabel/Label
 /*
@@ -239,6 +335,110 @@ RESPONSE{
   ]
 }
 */
+/// Transactions received by the addresses watched under a label, numbers parsed from cyphernode's strings
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelTxns {
+    pub label_txns: Vec<LabelTxn>,
+}
+impl LabelTxns {
+    /// Used internally to convert api json string to native struct
+    pub fn structify(stringified: &str) -> Result<LabelTxns, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelTxn {
+    pub label: String,
+    pub address: String,
+    pub txid: String,
+    #[serde(deserialize_with = "de::number")]
+    pub confirmations: u64,
+    /// None while unconfirmed
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub blockheight: Option<u64>,
+    #[serde(deserialize_with = "de::number")]
+    pub v_out: u32,
+    /// BTC
+    #[serde(deserialize_with = "de::number")]
+    pub amount: f64,
+    #[serde(default)]
+    pub blockhash: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub blocktime: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub timereceived: Option<u64>,
+}
+/// Lists the transactions of the addresses watched with label, most recent first. cyphernode returns 10 when count is None.
+pub async fn get_txns_by_watchlabel(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    label: String,
+    count: Option<u32>,
+) -> Result<LabelTxns, S5Error> {
+    let full_url: String = match count {
+        Some(count) => routes::GET_TXNS_BY_WATCHLABEL.url_with(&base_url, &[&label, &count.to_string()]),
+        None => routes::GET_TXNS_BY_WATCHLABEL.url_with(&base_url, &[&label]),
+    };
+    let text = http::get(client, &routes::GET_TXNS_BY_WATCHLABEL, &full_url, jwt).await?;
+    LabelTxns::structify(&text)
+}
+// GET http://cyphernode/get_unused_addresses_by_watchlabel/Label/4
+/*
+RESPONSE{
+  "label_unused_addresses": [
+    {
+      "pub32_watch_id": "1",
+      "pub32_label": "Label",
+      "pub32": "tpubD6NzVbkrYhZ4YR3QK2tyfMMvBghAvqtNaNK1LTyDWcRHLcMUm3ZN2cGm5BS3MhCRCeCkXQkTXXjiJgqxpqXK7PeUSp86DTTgkLpcjMtpKWk",
+      "address_pub32_index": "56",
+      "address": "tb1qxwpy6ku4pj26ug7jvxlaqgu7exzd0rgehqtw5h"
+    }
+  ]
+}
+*/
+/// Addresses derived from the xpub watched with a label that never received anything
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelUnusedAddresses {
+    pub label_unused_addresses: Vec<UnusedAddress>,
+}
+impl LabelUnusedAddresses {
+    /// Used internally to convert api json string to native struct
+    pub fn structify(stringified: &str) -> Result<LabelUnusedAddresses, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnusedAddress {
+    #[serde(deserialize_with = "de::number")]
+    pub pub32_watch_id: u64,
+    pub pub32_label: String,
+    pub pub32: String,
+    #[serde(deserialize_with = "de::number")]
+    pub address_pub32_index: u32,
+    pub address: String,
+}
+/// Lists unused addresses of the xpub watched with label, lowest index first. cyphernode returns 10 when count is None.
+pub async fn get_unused_addresses_by_watchlabel(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    label: String,
+    count: Option<u32>,
+) -> Result<LabelUnusedAddresses, S5Error> {
+    let full_url: String = match count {
+        Some(count) => routes::GET_UNUSED_ADDRESSES_BY_WATCHLABEL.url_with(&base_url, &[&label, &count.to_string()]),
+        None => routes::GET_UNUSED_ADDRESSES_BY_WATCHLABEL.url_with(&base_url, &[&label]),
+    };
+    let text = http::get(client, &routes::GET_UNUSED_ADDRESSES_BY_WATCHLABEL, &full_url, jwt).await?;
+    LabelUnusedAddresses::structify(&text)
+}
 // POST http://cyphernode/watchxpub
 /*
 REQUEST{
@@ -294,10 +494,7 @@ impl WatchXpubReq {
     pub fn stringify(&self) -> Result<String, S5Error> {
         match serde_json::to_string(&self.clone()) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::Internal(e.to_string())),
         }
     }
 }
@@ -320,42 +517,20 @@ impl WatchXpub {
     pub fn structify(stringified: &str) -> Result<WatchXpub, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 
 pub async fn watchxpub(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     body: WatchXpubReq,
-) -> Result<WatchXpub, String> {
-    let full_url: String = format!("https://{}/v0/watch", host).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
-
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.post(&full_url).json(&body).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match WatchXpub::structify(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
-    }
+) -> Result<WatchXpub, S5Error> {
+    let full_url: String = routes::WATCHXPUB.url(&base_url);
+    let text = http::post(client, &routes::WATCHXPUB, &full_url, jwt, &body).await?;
+    WatchXpub::structify(&text)
 }
 
 // GET http://cyphernode/unwatchxpubbyxpub/upub57Wa4MvRPNyAhxr578mQUdPr6MHwpg3Su875hj8K75AeUVZLXtFeiP52BrhNqDg93gjALU1MMh5UPRiiQPrwiTiuBBBRHzeyBMgrbwkmmkq
@@ -371,42 +546,53 @@ impl UnwatchXpub {
     pub fn structify(stringified: &str) -> Result<UnwatchXpub, S5Error> {
         match serde_json::from_str(stringified) {
             Ok(result) => Ok(result),
-            Err(e) => Err(S5Error::new(
-                ErrorKind::Internal,
-                &e.to_string(),
-            )),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
         }
     }
 }
 pub async fn unwatchxpubbyxpub(
-    host: String,
+    base_url: String,
     jwt: String,
-    cert: Certificate,
+    client: &Client,
     xpub: String,
-) -> Result<UnwatchXpub, String> {
-    let full_url: String = format!("https://{}/v0/unwatchxpubbyxpub/{}", host, xpub).to_string();
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", jwt).parse().unwrap());
+) -> Result<UnwatchXpub, S5Error> {
+    let full_url: String = routes::UNWATCHXPUBBYXPUB.url_with(&base_url, &[&xpub]);
+    let text = http::get(client, &routes::UNWATCHXPUBBYXPUB, &full_url, jwt).await?;
+    UnwatchXpub::structify(&text)
+}
 
-    let client = reqwest::Client::builder().add_root_certificate(cert);
-    let client = match client.default_headers(headers).build() {
-        Ok(result) => result,
-        Err(e) => return Err(e.to_string()),
-    };
-    match client.get(&full_url).send().await {
-        Ok(response) => match response.text().await {
-            Ok(text) => {
-                println!("{}", text);
-                match UnwatchXpub::structify(&text) {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(e.message),
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        },
-        Err(e) => Err(e.to_string()),
+// GET http://cyphernode/unwatchxpubbylabel/4421
+/*
+RESPONSE{
+  "event":"unwatchxpubbylabel",
+  "label":"4421"
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnwatchXpubLabel {
+    pub event: String,
+    pub label: String,
+}
+impl UnwatchXpubLabel {
+    /// Used internally to convert api json string to native struct
+    pub fn structify(stringified: &str) -> Result<UnwatchXpubLabel, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
     }
 }
+/// Stops watching the xpub watched with label, and all its derived addresses
+pub async fn unwatchxpubbylabel(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    label: String,
+) -> Result<UnwatchXpubLabel, S5Error> {
+    let full_url: String = routes::UNWATCHXPUBBYLABEL.url_with(&base_url, &[&label]);
+    let text = http::get(client, &routes::UNWATCHXPUBBYLABEL, &full_url, jwt).await?;
+    UnwatchXpubLabel::structify(&text)
+}
 
 // GET http://cyphernode/getactivexpubwatches
 /*
@@ -424,6 +610,282 @@ RESPONSE{
   ]
 }
 */
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XpubWatches {
+    pub watches: Vec<XpubWatch>,
+}
+impl XpubWatches {
+    /// Used internally to convert api json string to native struct
+    pub fn structify(stringified: &str) -> Result<XpubWatches, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XpubWatch {
+    #[serde(deserialize_with = "de::number")]
+    pub id: u64,
+    pub pub32: String,
+    pub label: String,
+    pub derivation_path: String,
+    /// Highest derivation index imported in the watching wallet so far
+    #[serde(deserialize_with = "de::number")]
+    pub last_imported_n: u64,
+    #[serde(rename = "unconfirmedCallbackURL")]
+    pub unconfirmed_callback_url: Option<String>,
+    #[serde(rename = "confirmedCallbackURL")]
+    pub confirmed_callback_url: Option<String>,
+    pub watching_since: String,
+}
+/// Lists every xpub currently watched
+pub async fn getactivexpubwatches(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+) -> Result<XpubWatches, S5Error> {
+    let full_url: String = routes::GETACTIVEXPUBWATCHES.url(&base_url);
+    let text = http::get(client, &routes::GETACTIVEXPUBWATCHES, &full_url, jwt).await?;
+    XpubWatches::structify(&text)
+}
+
+// GET http://cyphernode/getactivewatchesbyxpub/tpubD6NzVbkrYhZ4YR3QK2tyfMMvBghAvqtNaNK1LTyDWcRHLcMUm3ZN2cGm5BS3MhCRCeCkXQkTXXjiJgqxpqXK7PeUSp86DTTgkLpcjMtpKWk
+// GET http://cyphernode/getactivewatchesbylabel/2219
+/*
+RESPONSE{
+  "watches": [
+  {
+  "id":"291",
+  "address":"2N6Q9kBcLtNswgMSLSQ5oduhbctk7hxEJW8",
+  "imported":"1",
+  "unconfirmedCallbackURL":"192.168.133.233:1111/callback0conf",
+  "confirmedCallbackURL":"192.168.133.233:1111/callback1conf",
+  "watching_since":"2018-09-06 21:14:03",
+  "derivation_path":"1/0/n",
+  "pub32_index":"0"}
+  ]
+}
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XpubAddressWatches {
+    pub watches: Vec<XpubAddressWatch>,
+}
+impl XpubAddressWatches {
+    /// Used internally to convert api json string to native struct
+    pub fn structify(stringified: &str) -> Result<XpubAddressWatches, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XpubAddressWatch {
+    #[serde(deserialize_with = "de::number")]
+    pub id: u64,
+    pub address: String,
+    #[serde(deserialize_with = "de::flag")]
+    pub imported: bool,
+    #[serde(rename = "unconfirmedCallbackURL")]
+    pub unconfirmed_callback_url: Option<String>,
+    #[serde(rename = "confirmedCallbackURL")]
+    pub confirmed_callback_url: Option<String>,
+    pub watching_since: String,
+    pub derivation_path: String,
+    #[serde(deserialize_with = "de::number")]
+    pub pub32_index: u64,
+}
+/// Lists the derived addresses watched for an xpub
+pub async fn getactivewatchesbyxpub(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    xpub: String,
+) -> Result<XpubAddressWatches, S5Error> {
+    let full_url: String = routes::GETACTIVEWATCHESBYXPUB.url_with(&base_url, &[&xpub]);
+    let text = http::get(client, &routes::GETACTIVEWATCHESBYXPUB, &full_url, jwt).await?;
+    XpubAddressWatches::structify(&text)
+}
+/// Lists the derived addresses watched for the xpub watched with label
+pub async fn getactivewatchesbylabel(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    label: String,
+) -> Result<XpubAddressWatches, S5Error> {
+    let full_url: String = routes::GETACTIVEWATCHESBYLABEL.url_with(&base_url, &[&label]);
+    let text = http::get(client, &routes::GETACTIVEWATCHESBYLABEL, &full_url, jwt).await?;
+    XpubAddressWatches::structify(&text)
+}
+
+// GET http://cyphernode/deriveindex/25-30
+// derives with the xpub and path (eg. 0/n) configured in cyphernode
+// POST http://cyphernode/derivepubpath
+/*
+REQUEST{
+  "pub32":"upub5GtUcgGed1aGH4HKQ3vMYrsmLXwmHhS1AeX33ZvDgZiyvkGhNTvGd2TA5Lr4v239Fzjj4ZY48t6wTtXUy2yRgapf37QHgt6KWEZ6bgsCLpb",
+  "path":"0/25-30"
+}
+RESPONSE{
+  "addresses":[
+    {"address":"2N6Q9kBcLtNswgMSLSQ5oduhbctk7hxEJW8"},
+    {"address":"2NFLhFghAPKEPuZCKoeXYYxuaBxhKXbmhBV"},
+    ...
+  ]
+}
+*/
+// GET http://cyphernode/deriveindex_bitcoind/25-30
+// POST http://cyphernode/derivepubpath_bitcoind
+// same as above, derived by bitcoind's deriveaddresses
+/*
+RESPONSE[
+  "2N6Q9kBcLtNswgMSLSQ5oduhbctk7hxEJW8",
+  "2NFLhFghAPKEPuZCKoeXYYxuaBxhKXbmhBV",
+  ...
+]
+*/
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerivePubPathReq {
+    pub pub32: String,
+    /// eg. 0/25-30
+    pub path: String,
+}
+impl DerivePubPathReq {
+    /// path is the parent of the range, eg. "0" for the receive chain; "" derives the range directly under pub32
+    pub fn new(pub32: String, path: &str, range: &RangeInclusive<u32>) -> Self {
+        DerivePubPathReq {
+            pub32,
+            path: format!("{}{}", parent_prefix(path), index_range(range)),
+        }
+    }
+}
+/// An address derived at index, with its path below pub32 when the client knows it
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerivedAddress {
+    pub index: u32,
+    /// None for deriveindex, whose path is configured in cyphernode
+    pub path: Option<String>,
+    pub address: String,
+}
+/// Both response shapes: pycoin's addresses objects and bitcoind's plain list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DerivedAddresses {
+    Objects { addresses: Vec<DerivedAddressObject> },
+    List(Vec<String>),
+}
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerivedAddressObject {
+    pub address: String,
+}
+impl DerivedAddresses {
+    /// Used internally to convert api json string to native struct
+    pub fn structify(stringified: &str) -> Result<DerivedAddresses, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+    /// Pairs the addresses, in order, with the indexes of range
+    pub fn pair(
+        self,
+        range: &RangeInclusive<u32>,
+        parent: Option<&str>,
+        stringified: &str,
+    ) -> Result<Vec<DerivedAddress>, S5Error> {
+        let addresses: Vec<String> = match self {
+            DerivedAddresses::Objects { addresses } => {
+                addresses.into_iter().map(|object| object.address).collect()
+            }
+            DerivedAddresses::List(addresses) => addresses,
+        };
+        if addresses.len() != range.clone().count() {
+            return Err(S5Error::Deserialize {
+                message: format!("expected {} addresses for {}", range.clone().count(), index_range(range)),
+                body: stringified.to_string(),
+            });
+        }
+        Ok(range
+            .clone()
+            .zip(addresses)
+            .map(|(index, address)| DerivedAddress {
+                index,
+                path: parent.map(|parent| format!("{}{}", parent_prefix(parent), index)),
+                address,
+            })
+            .collect())
+    }
+}
+fn parent_prefix(path: &str) -> String {
+    match path.trim_matches('/') {
+        "" => String::new(),
+        parent => format!("{}/", parent),
+    }
+}
+fn index_range(range: &RangeInclusive<u32>) -> String {
+    format!("{}-{}", range.start(), range.end())
+}
+fn check_range(range: &RangeInclusive<u32>) -> Result<(), S5Error> {
+    if range.is_empty() {
+        return Err(S5Error::Input(format!("Empty derivation range {}", index_range(range))));
+    }
+    Ok(())
+}
+///Derives addresses at the range of indexes of the xpub and path configured in cyphernode (pycoin)
+pub async fn deriveindex(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    range: RangeInclusive<u32>,
+) -> Result<Vec<DerivedAddress>, S5Error> {
+    check_range(&range)?;
+    let full_url: String = routes::DERIVEINDEX.url_with(&base_url, &[&index_range(&range)]);
+    let text = http::get(client, &routes::DERIVEINDEX, &full_url, jwt).await?;
+    DerivedAddresses::structify(&text)?.pair(&range, None, &text)
+}
+///Derives addresses at path/range below pub32 (pycoin)
+pub async fn derivepubpath(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    pub32: String,
+    path: String,
+    range: RangeInclusive<u32>,
+) -> Result<Vec<DerivedAddress>, S5Error> {
+    check_range(&range)?;
+    let body = DerivePubPathReq::new(pub32, &path, &range);
+    let full_url: String = routes::DERIVEPUBPATH.url(&base_url);
+    let text = http::post(client, &routes::DERIVEPUBPATH, &full_url, jwt, &body).await?;
+    DerivedAddresses::structify(&text)?.pair(&range, Some(&path), &text)
+}
+///Same as deriveindex, derived by bitcoind
+pub async fn deriveindex_bitcoind(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    range: RangeInclusive<u32>,
+) -> Result<Vec<DerivedAddress>, S5Error> {
+    check_range(&range)?;
+    let full_url: String = routes::DERIVEINDEX_BITCOIND.url_with(&base_url, &[&index_range(&range)]);
+    let text = http::get(client, &routes::DERIVEINDEX_BITCOIND, &full_url, jwt).await?;
+    DerivedAddresses::structify(&text)?.pair(&range, None, &text)
+}
+///Same as derivepubpath, derived by bitcoind
+pub async fn derivepubpath_bitcoind(
+    base_url: String,
+    jwt: String,
+    client: &Client,
+    pub32: String,
+    path: String,
+    range: RangeInclusive<u32>,
+) -> Result<Vec<DerivedAddress>, S5Error> {
+    check_range(&range)?;
+    let body = DerivePubPathReq::new(pub32, &path, &range);
+    let full_url: String = routes::DERIVEPUBPATH_BITCOIND.url(&base_url);
+    let text = http::post(client, &routes::DERIVEPUBPATH_BITCOIND, &full_url, jwt, &body).await?;
+    DerivedAddresses::structify(&text)?.pair(&range, Some(&path), &text)
+}
 
 // GET http://cyphernode/executecallbacks
 // executes the callbacks that would be usually executed when "conf" is called by the node.
@@ -466,4 +928,43 @@ ONECONF{
   "blockheight":""
 }
 */
-//
+/// Posted by cyphernode to the unconfirmedCallbackURL (0 conf) and confirmedCallbackURL (1 conf) of a watch.
+/// pub32 fields are only set when the address was derived from a watched xpub.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchCallback {
+    pub id: String,
+    pub address: String,
+    pub hash: String,
+    pub vout_n: i64,
+    pub sent_amount: f64,
+    pub confirmations: i64,
+    pub received: String,
+    pub size: i64,
+    pub vsize: i64,
+    pub fees: f64,
+    pub replaceable: bool,
+    pub blockhash: String,
+    pub blocktime: String,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub blockheight: Option<u64>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default, rename = "eventMessage")]
+    pub event_message: Option<String>,
+    #[serde(default)]
+    pub pub32: Option<String>,
+    #[serde(default)]
+    pub pub32_label: Option<String>,
+    #[serde(default)]
+    pub pub32_derivation_path: Option<String>,
+}
+impl WatchCallback {
+    /// Used internally to convert api json string to native struct
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(stringified: &str) -> Result<WatchCallback, S5Error> {
+        match serde_json::from_str(stringified) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(S5Error::deserialize(e, stringified)),
+        }
+    }
+}